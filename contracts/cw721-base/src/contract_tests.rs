@@ -1,6 +1,8 @@
 #![cfg(test)]
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-use cosmwasm_std::{from_binary, to_binary, CosmosMsg, DepsMut, Empty, Response, WasmMsg};
+use cosmwasm_std::{
+    from_binary, to_binary, CosmosMsg, DepsMut, Empty, Response, Timestamp, WasmMsg,
+};
 
 use cw721::{
     ApprovedForAllResponse, ContractInfoResponse, Cw721Query, Cw721ReceiveMsg, Expiration,
@@ -15,12 +17,40 @@ const MINTER: &str = "merlin";
 const CONTRACT_NAME: &str = "Magic Power";
 const SYMBOL: &str = "MGK";
 
+fn mint_token(
+    contract: &Cw721Contract<'static, Extension, Empty>,
+    deps: DepsMut<'_>,
+    token_id: &str,
+    owner: &str,
+    extension: Extension,
+) {
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: token_id.to_string(),
+        owner: String::from(owner),
+        token_uri: None,
+        extension,
+    });
+    contract
+        .execute(deps, mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+}
+
+fn locked_until(unlock_date: Option<Timestamp>) -> Extension {
+    Extension {
+        athlete_id: "0".to_string(),
+        is_locked: true,
+        unlock_date,
+        ..Extension::default()
+    }
+}
+
 fn setup_contract(deps: DepsMut<'_>) -> Cw721Contract<'static, Extension, Empty> {
     let contract = Cw721Contract::default();
     let msg = InstantiateMsg {
         name: CONTRACT_NAME.to_string(),
         symbol: SYMBOL.to_string(),
         minter: String::from(MINTER),
+        game_contracts: vec![],
     };
     let info = mock_info("creator", &[]);
     let res = contract.instantiate(deps, mock_env(), info, msg).unwrap();
@@ -37,6 +67,7 @@ fn proper_instantiation() {
         name: CONTRACT_NAME.to_string(),
         symbol: SYMBOL.to_string(),
         minter: String::from(MINTER),
+        game_contracts: vec![],
     };
     let info = mock_info("creator", &[]);
 
@@ -78,7 +109,7 @@ fn minting() {
         token_id: token_id.clone(),
         owner: String::from("medusa"),
         token_uri: Some(token_uri.clone()),
-        extension: None,
+        extension: Extension::default(),
     });
 
     // random cannot mint
@@ -86,7 +117,7 @@ fn minting() {
     let err = contract
         .execute(deps.as_mut(), mock_env(), random, mint_msg.clone())
         .unwrap_err();
-    assert_eq!(err, ContractError::Unauthorized {});
+    assert!(matches!(err, ContractError::Unauthorized {}));
 
     // minter can mint
     let allowed = mock_info(MINTER, &[]);
@@ -109,7 +140,7 @@ fn minting() {
         info,
         NftInfoResponse::<Extension> {
             token_uri: Some(token_uri),
-            extension: None,
+            extension: Extension::default(),
         }
     );
 
@@ -130,14 +161,14 @@ fn minting() {
         token_id: token_id.clone(),
         owner: String::from("hercules"),
         token_uri: None,
-        extension: None,
+        extension: Extension::default(),
     });
 
     let allowed = mock_info(MINTER, &[]);
     let err = contract
         .execute(deps.as_mut(), mock_env(), allowed, mint_msg2)
         .unwrap_err();
-    assert_eq!(err, ContractError::Claimed {});
+    assert!(matches!(err, ContractError::Claimed {}));
 
     // list the token_ids
     let tokens = contract.all_tokens(deps.as_ref(), None, None).unwrap();
@@ -158,7 +189,7 @@ fn transferring_nft() {
         token_id: token_id.clone(),
         owner: String::from("venus"),
         token_uri: Some(token_uri),
-        extension: None,
+        extension: Extension::default(),
     });
 
     let minter = mock_info(MINTER, &[]);
@@ -176,7 +207,7 @@ fn transferring_nft() {
     let err = contract
         .execute(deps.as_mut(), mock_env(), random, transfer_msg)
         .unwrap_err();
-    assert_eq!(err, ContractError::Unauthorized {});
+    assert!(matches!(err, ContractError::Unauthorized {}));
 
     // owner can
     let random = mock_info("venus", &[]);
@@ -212,7 +243,7 @@ fn sending_nft() {
         token_id: token_id.clone(),
         owner: String::from("venus"),
         token_uri: Some(token_uri),
-        extension: None,
+        extension: Extension::default(),
     });
 
     let minter = mock_info(MINTER, &[]);
@@ -232,7 +263,7 @@ fn sending_nft() {
     let err = contract
         .execute(deps.as_mut(), mock_env(), random, send_msg.clone())
         .unwrap_err();
-    assert_eq!(err, ContractError::Unauthorized {});
+    assert!(matches!(err, ContractError::Unauthorized {}));
 
     // but owner can
     let random = mock_info("venus", &[]);
@@ -278,7 +309,7 @@ fn approving_revoking() {
         token_id: token_id.clone(),
         owner: String::from("demeter"),
         token_uri: Some(token_uri),
-        extension: None,
+        extension: Extension::default(),
     });
 
     let minter = mock_info(MINTER, &[]);
@@ -385,7 +416,7 @@ fn approving_all_revoking_all() {
         token_id: token_id1.clone(),
         owner: String::from("demeter"),
         token_uri: Some(token_uri1),
-        extension: None,
+        extension: Extension::default(),
     });
 
     let minter = mock_info(MINTER, &[]);
@@ -397,7 +428,7 @@ fn approving_all_revoking_all() {
         token_id: token_id2.clone(),
         owner: String::from("demeter"),
         token_uri: Some(token_uri2),
-        extension: None,
+        extension: Extension::default(),
     });
 
     contract
@@ -601,7 +632,7 @@ fn query_tokens_by_owner() {
         token_id: token_id1.clone(),
         owner: demeter.clone(),
         token_uri: None,
-        extension: None,
+        extension: Extension::default(),
     });
     contract
         .execute(deps.as_mut(), mock_env(), minter.clone(), mint_msg)
@@ -611,7 +642,7 @@ fn query_tokens_by_owner() {
         token_id: token_id2.clone(),
         owner: ceres.clone(),
         token_uri: None,
-        extension: None,
+        extension: Extension::default(),
     });
     contract
         .execute(deps.as_mut(), mock_env(), minter.clone(), mint_msg)
@@ -621,7 +652,7 @@ fn query_tokens_by_owner() {
         token_id: token_id3.clone(),
         owner: demeter.clone(),
         token_uri: None,
-        extension: None,
+        extension: Extension::default(),
     });
     contract
         .execute(deps.as_mut(), mock_env(), minter, mint_msg)
@@ -660,3 +691,274 @@ fn query_tokens_by_owner() {
         .unwrap();
     assert_eq!(&by_demeter[1..], &tokens.tokens[..]);
 }
+
+#[test]
+fn burning_nft() {
+    let mut deps = mock_dependencies(&[]);
+    let contract = setup_contract(deps.as_mut());
+
+    mint_token(&contract, deps.as_mut(), "owned", "demeter", Extension::default());
+    mint_token(&contract, deps.as_mut(), "approved", "demeter", Extension::default());
+    mint_token(&contract, deps.as_mut(), "operated", "demeter", Extension::default());
+    mint_token(&contract, deps.as_mut(), "kept", "demeter", Extension::default());
+    assert_eq!(4, contract.num_tokens(deps.as_ref()).unwrap().count);
+
+    // random cannot burn
+    let burn_msg = ExecuteMsg::Burn {
+        token_id: "owned".to_string(),
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info("random", &[]), burn_msg.clone())
+        .unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+
+    // owner can
+    let res = contract
+        .execute(deps.as_mut(), mock_env(), mock_info("demeter", &[]), burn_msg.clone())
+        .unwrap();
+    assert_eq!(
+        res,
+        Response::new()
+            .add_attribute("action", "burn")
+            .add_attribute("sender", "demeter")
+            .add_attribute("token_id", "owned")
+    );
+    assert_eq!(3, contract.num_tokens(deps.as_ref()).unwrap().count);
+    let _ = contract
+        .nft_info(deps.as_ref(), "owned".to_string())
+        .unwrap_err();
+
+    // cannot burn twice
+    let _ = contract
+        .execute(deps.as_mut(), mock_env(), mock_info("demeter", &[]), burn_msg)
+        .unwrap_err();
+    assert_eq!(3, contract.num_tokens(deps.as_ref()).unwrap().count);
+
+    // approved spender can
+    let approve_msg = ExecuteMsg::Approve {
+        spender: String::from("ceres"),
+        token_id: "approved".to_string(),
+        expires: None,
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info("demeter", &[]), approve_msg)
+        .unwrap();
+    let burn_msg = ExecuteMsg::Burn {
+        token_id: "approved".to_string(),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info("ceres", &[]), burn_msg)
+        .unwrap();
+    assert_eq!(2, contract.num_tokens(deps.as_ref()).unwrap().count);
+
+    // operator can
+    let approve_all_msg = ExecuteMsg::ApproveAll {
+        operator: String::from("hermes"),
+        expires: None,
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info("demeter", &[]), approve_all_msg)
+        .unwrap();
+    let burn_msg = ExecuteMsg::Burn {
+        token_id: "operated".to_string(),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info("hermes", &[]), burn_msg)
+        .unwrap();
+    assert_eq!(1, contract.num_tokens(deps.as_ref()).unwrap().count);
+
+    // burned tokens are gone from the owner index
+    let tokens = contract
+        .tokens(deps.as_ref(), String::from("demeter"), None, None)
+        .unwrap();
+    assert_eq!(vec!["kept".to_string()], tokens.tokens);
+}
+
+#[test]
+fn locked_tokens_cannot_move() {
+    let mut deps = mock_dependencies(&[]);
+    let contract = setup_contract(deps.as_mut());
+
+    let unlock_date = mock_env().block.time.plus_seconds(100);
+    mint_token(&contract, deps.as_mut(), "locked", "venus", locked_until(Some(unlock_date)));
+    mint_token(&contract, deps.as_mut(), "forever", "venus", locked_until(None));
+
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("random"),
+        token_id: "locked".to_string(),
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info("venus", &[]), transfer_msg.clone())
+        .unwrap_err();
+    assert!(matches!(err, ContractError::TokenLocked {}));
+
+    let send_msg = ExecuteMsg::SendNft {
+        contract: String::from("another_contract"),
+        token_id: "locked".to_string(),
+        msg: to_binary("You now have the melting power").unwrap(),
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info("venus", &[]), send_msg)
+        .unwrap_err();
+    assert!(matches!(err, ContractError::TokenLocked {}));
+
+    let burn_msg = ExecuteMsg::Burn {
+        token_id: "locked".to_string(),
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info("venus", &[]), burn_msg)
+        .unwrap_err();
+    assert!(matches!(err, ContractError::TokenLocked {}));
+
+    // a lock without an unlock date does not expire
+    let mut later = mock_env();
+    later.block.time = unlock_date;
+    let burn_msg = ExecuteMsg::Burn {
+        token_id: "forever".to_string(),
+    };
+    let err = contract
+        .execute(deps.as_mut(), later.clone(), mock_info("venus", &[]), burn_msg)
+        .unwrap_err();
+    assert!(matches!(err, ContractError::TokenLocked {}));
+
+    // the token can move once its unlock date has passed
+    contract
+        .execute(deps.as_mut(), later, mock_info("venus", &[]), transfer_msg)
+        .unwrap();
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "locked".to_string(), false)
+        .unwrap();
+    assert_eq!(owner.owner, "random");
+    assert_eq!(2, contract.num_tokens(deps.as_ref()).unwrap().count);
+}
+
+#[test]
+fn only_game_contracts_clear_locks() {
+    let mut deps = mock_dependencies(&[]);
+    let contract = setup_contract(deps.as_mut());
+
+    mint_token(&contract, deps.as_mut(), "locked", "venus", locked_until(None));
+
+    // only the minter sets the game contracts
+    let update_msg = ExecuteMsg::UpdateGameContracts {
+        game_contracts: vec![String::from("game")],
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info("random", &[]), update_msg.clone())
+        .unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), update_msg)
+        .unwrap();
+
+    let unlock_msg = ExecuteMsg::UpdateToken {
+        token_id: "locked".to_string(),
+        token_uri: None,
+        extension: Some(Extension {
+            athlete_id: "0".to_string(),
+            ..Extension::default()
+        }),
+    };
+
+    // neither a random address, the owner nor the minter can unlock
+    for sender in ["random", "venus", MINTER].iter() {
+        let err = contract
+            .execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), unlock_msg.clone())
+            .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+    }
+
+    // the minter can still update a locked token as long as it stays locked
+    let lock_msg = ExecuteMsg::UpdateToken {
+        token_id: "locked".to_string(),
+        token_uri: None,
+        extension: Some(Extension {
+            usage: 3,
+            ..locked_until(None)
+        }),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), lock_msg)
+        .unwrap();
+    let info = contract
+        .nft_info(deps.as_ref(), "locked".to_string())
+        .unwrap();
+    assert!(info.extension.is_locked);
+    assert_eq!(3, info.extension.usage);
+
+    // a game contract can
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info("game", &[]), unlock_msg)
+        .unwrap();
+    let info = contract
+        .nft_info(deps.as_ref(), "locked".to_string())
+        .unwrap();
+    assert!(!info.extension.is_locked);
+
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("random"),
+        token_id: "locked".to_string(),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info("venus", &[]), transfer_msg)
+        .unwrap();
+}
+
+#[test]
+fn minter_cannot_shorten_locks() {
+    let mut deps = mock_dependencies(&[]);
+    let contract = setup_contract(deps.as_mut());
+
+    let unlock_date = mock_env().block.time.plus_seconds(100);
+    mint_token(&contract, deps.as_mut(), "locked", "venus", locked_until(Some(unlock_date)));
+    mint_token(&contract, deps.as_mut(), "forever", "venus", locked_until(None));
+
+    let update = |token_id: &str, extension: Option<Extension>| ExecuteMsg::UpdateToken {
+        token_id: token_id.to_string(),
+        token_uri: None,
+        extension,
+    };
+
+    // an unlock date in the past clears the lock as much as the flag does
+    let expired = locked_until(Some(mock_env().block.time.minus_seconds(1)));
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), update("locked", Some(expired)))
+        .unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+
+    // a lock cannot end earlier, even if the token stays locked for now
+    let earlier = locked_until(Some(unlock_date.minus_seconds(50)));
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), update("locked", Some(earlier.clone())))
+        .unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), update("forever", Some(earlier)))
+        .unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+
+    // but it can be extended
+    let later = locked_until(Some(unlock_date.plus_seconds(50)));
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), update("locked", Some(later)))
+        .unwrap();
+    let info = contract
+        .nft_info(deps.as_ref(), "locked".to_string())
+        .unwrap();
+    assert_eq!(Some(unlock_date.plus_seconds(50)), info.extension.unlock_date);
+
+    // a missing extension keeps the current one
+    let msg = ExecuteMsg::UpdateToken {
+        token_id: "forever".to_string(),
+        token_uri: Some("uri".to_string()),
+        extension: None,
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), msg)
+        .unwrap();
+    let info = contract
+        .nft_info(deps.as_ref(), "forever".to_string())
+        .unwrap();
+    assert_eq!(Some("uri".to_string()), info.token_uri);
+    assert_eq!(locked_until(None), info.extension);
+}
@@ -15,6 +15,9 @@ pub enum ContractError {
     #[error("Cannot set approval that is already expired")]
     Expired {},
 
+    #[error("Token is locked for a game")]
+    TokenLocked {},
+
     #[error(transparent)]
     JsonSerError(#[from] serde_json_wasm::ser::Error)
 }
@@ -36,6 +36,12 @@ where
         self.contract_info.save(deps.storage, &info)?;
         let minter = deps.api.addr_validate(&msg.minter)?;
         self.minter.save(deps.storage, &minter)?;
+        let game_contracts = msg
+            .game_contracts
+            .iter()
+            .map(|addr| deps.api.addr_validate(addr))
+            .collect::<StdResult<Vec<_>>>()?;
+        self.game_contracts.save(deps.storage, &game_contracts)?;
         Ok(Response::default())
     }
 
@@ -48,6 +54,7 @@ where
     ) -> Result<Response<C>, ContractError> {
         match msg {
             ExecuteMsg::Mint(msg) => self.mint(deps, env, info, msg),
+            ExecuteMsg::Burn { token_id } => self.burn(deps, env, info, token_id),
             ExecuteMsg::Approve {
                 spender,
                 token_id,
@@ -72,6 +79,9 @@ where
             ExecuteMsg::UpdateMinter {
                 minter,
             } => self.update_minter(deps, info, minter),
+            ExecuteMsg::UpdateGameContracts {
+                game_contracts,
+            } => self.update_game_contracts(deps, info, game_contracts),
             ExecuteMsg::UpdateToken {
                 token_id,
                 token_uri,
@@ -106,7 +116,7 @@ where
 // TODO pull this into some sort of trait extension??
 impl<'a, T, C> Cw721Contract<'a, T, C>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Serialize + DeserializeOwned + Clone + PlayiblePersonalization,
     C: CustomMsg,
 {
     pub fn mint(
@@ -143,6 +153,30 @@ where
             .add_attribute("token_id", msg.token_id))
     }
 
+    pub fn burn(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+    ) -> Result<Response<C>, ContractError> {
+        let token = self.tokens.load(deps.storage, &token_id)?;
+        // same permissions as a transfer
+        self.check_can_send(deps.as_ref(), &env, &info, &token)?;
+        if is_locked(&token.extension, &env) {
+            return Err(ContractError::TokenLocked {});
+        }
+
+        // removes the token from the owner index as well
+        self.tokens.remove(deps.storage, &token_id)?;
+        self.decrement_tokens(deps.storage)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "burn")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id))
+    }
+
     pub fn update_minter(
         &self,
         deps: DepsMut,
@@ -165,19 +199,63 @@ where
             .add_attribute("new_minter", minter))
     }
     
+    pub fn update_game_contracts(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        game_contracts: Vec<String>,
+    ) -> Result<Response<C>, ContractError> {
+        let minter = self.minter.load(deps.storage)?;
+
+        if info.sender != minter {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        let game_contracts = game_contracts
+            .iter()
+            .map(|addr| deps.api.addr_validate(addr))
+            .collect::<StdResult<Vec<_>>>()?;
+        self.game_contracts.save(deps.storage, &game_contracts)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "update_game_contracts")
+            .add_attribute("sender", info.sender))
+    }
+
     pub fn update_token(
         &self,
         deps: DepsMut,
-        _env: Env,
-        _info: MessageInfo,
+        env: Env,
+        info: MessageInfo,
         token_id: String,
         token_uri: Option<String>,  
         extension: Option<T>
     ) -> Result<Response<C>, ContractError> {
+        let minter = self.minter.load(deps.storage)?;
+        let is_game_contract = self.game_contracts(deps.storage)?.contains(&info.sender);
+
+        if info.sender != minter && !is_game_contract {
+            return Err(ContractError::Unauthorized {});
+        }
+
         let mut token = self.tokens.load(deps.storage, &token_id)?;
+        // the extension is kept if none is given
+        let extension = extension.unwrap_or_else(|| token.extension.clone());
+
+        // only game contracts can clear a lock or move its unlock date earlier
+        if !is_game_contract && is_locked(&token.extension, &env) {
+            let unlocks_earlier = match (token.extension.get_unlock_date(), extension.get_unlock_date()) {
+                (Some(current), Some(new)) => new < current,
+                (None, Some(_)) => true,
+                (_, None) => false,
+            };
+            if !is_locked(&extension, &env) || unlocks_earlier {
+                return Err(ContractError::Unauthorized {});
+            }
+        }
 
         token.token_uri = token_uri;
-        token.extension = extension.unwrap();
+        token.extension = extension;
 
         self.tokens.save(deps.storage, &token_id.clone(), &token)?;
 
@@ -233,7 +311,7 @@ where
 
 impl<'a, T, C> Cw721Execute<T, C> for Cw721Contract<'a, T, C>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Serialize + DeserializeOwned + Clone + PlayiblePersonalization,
     C: CustomMsg,
 {
     type Err = ContractError;
@@ -363,7 +441,7 @@ where
 // helpers
 impl<'a, T, C> Cw721Contract<'a, T, C>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Serialize + DeserializeOwned + Clone + PlayiblePersonalization,
     C: CustomMsg,
 {
     pub fn _transfer_nft(
@@ -377,6 +455,10 @@ where
         let mut token = self.tokens.load(deps.storage, &token_id)?;
         // ensure we have permissions
         self.check_can_send(deps.as_ref(), env, info, &token)?;
+        // locked tokens stay with their owner until the unlock date
+        if is_locked(&token.extension, env) {
+            return Err(ContractError::TokenLocked {});
+        }
         // set owner and remove existing approvals
         token.owner = deps.api.addr_validate(recipient)?;
         token.approvals = vec![];
@@ -493,3 +575,11 @@ where
         }
     }
 }
+
+/// returns true if the token is locked and its unlock date, if any, has not passed yet
+fn is_locked<T: PlayiblePersonalization>(extension: &T, env: &Env) -> bool {
+    extension.get_is_locked()
+        && extension
+            .get_unlock_date()
+            .map_or(true, |unlock_date| env.block.time < unlock_date)
+}
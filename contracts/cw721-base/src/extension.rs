@@ -2,12 +2,41 @@ use cosmwasm_std::Timestamp;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+/// Rarity of an athlete token, serialized as its one letter code
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, JsonSchema, Debug)]
+pub enum Rarity {
+    #[serde(rename = "C")]
+    Common,
+    #[serde(rename = "U")]
+    Uncommon,
+    #[serde(rename = "R")]
+    Rare,
+    #[serde(rename = "L")]
+    Legendary,
+}
+
+impl Rarity {
+    /// All rarities from the lowest to the highest
+    pub const ALL: [Rarity; 4] = [
+        Rarity::Common,
+        Rarity::Uncommon,
+        Rarity::Rare,
+        Rarity::Legendary,
+    ];
+}
+
+impl Default for Rarity {
+    fn default() -> Self {
+        Rarity::Common
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
 pub struct PlayibleInfo {
     /// Reference ID of the Athlete Token
     pub athlete_id: String,
     /// Describes the rarity of the NFT 
-    pub rarity: String,
+    pub rarity: Rarity,
     /// Determines whether or not the NFT is locked for Fantasy Sports
     pub is_locked: bool,
     /// Determines the unlock date after the NFT has been locked
@@ -20,8 +49,8 @@ pub trait PlayiblePersonalization {
     fn get_athlete_id(&self) -> String;
     fn set_athlete_id(&mut self, athlete_id: &str);
 
-    fn get_rarity(&self) -> String;
-    fn set_rarity(&mut self, rarity: &str);
+    fn get_rarity(&self) -> Rarity;
+    fn set_rarity(&mut self, rarity: Rarity);
 
     fn get_is_locked(&self) -> bool;
     fn set_is_locked(&mut self, is_locked: bool);
@@ -41,11 +70,11 @@ impl PlayiblePersonalization for PlayibleInfo {
         self.athlete_id = String::from(athlete_id)
     }
 
-    fn get_rarity(&self) -> String {
-        self.rarity.clone()
+    fn get_rarity(&self) -> Rarity {
+        self.rarity
     }
-    fn set_rarity(&mut self, rarity: &str) {
-        self.rarity = String::from(rarity)
+    fn set_rarity(&mut self, rarity: Rarity) {
+        self.rarity = rarity
     }
 
     fn get_is_locked(&self) -> bool {
@@ -1,4 +1,4 @@
-// mod contract_tests;
+mod contract_tests;
 mod error;
 mod execute;
 mod extension;
@@ -7,9 +7,9 @@ mod query;
 pub mod state;
 
 pub use crate::error::ContractError;
+pub use crate::extension::{PlayibleInfo, PlayiblePersonalization, Rarity};
 pub use crate::msg::{ExecuteMsg, InstantiateMsg, MintMsg, MinterResponse, QueryMsg};
 pub use crate::state::Cw721Contract;
-use cosmwasm_std::Empty;
 
 // This is a simple type to let us handle empty extensions
 pub type Extension = extension::PlayibleInfo;
@@ -19,7 +19,7 @@ pub mod entry {
     use super::*;
 
     use cosmwasm_std::entry_point;
-    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+    use cosmwasm_std::{Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdResult};
 
     // This makes a conscious choice on the various generics used by the contract
     #[entry_point]
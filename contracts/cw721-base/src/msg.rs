@@ -18,6 +18,9 @@ pub struct InstantiateMsg {
     /// This is designed for a base NFT that is controlled by an external program
     /// or contract. You will likely replace this with custom logic in custom NFTs
     pub minter: String,
+    /// Game contracts allowed to clear the lock of tokens
+    #[serde(default)]
+    pub game_contracts: Vec<String>,
 }
 
 /// This is like Cw721ExecuteMsg but we add a Mint command for an owner
@@ -59,10 +62,18 @@ where
     /// Mint a new NFT, can only be called by the contract minter
     Mint(MintMsg<T>),
 
+    /// Destroys a token, can be called by the owner, an approved spender or an operator
+    Burn { token_id: String },
+
     /// Updates authorized minter
     UpdateMinter { minter: String },
 
-    /// Updates token metadata
+    /// Replaces the game contracts allowed to clear token locks, can only be called by the minter
+    UpdateGameContracts { game_contracts: Vec<String> },
+
+    /// Updates token metadata, can only be called by the minter or a game contract.
+    /// The extension is kept if none is given. Only game contracts can unlock a locked token
+    /// or move its unlock date earlier
     UpdateToken {
         token_id: String,
         token_uri: Option<String>,
@@ -155,6 +166,9 @@ pub enum QueryMsg {
     // Return the minter
     Minter {},
 
+    /// Returns the game contracts allowed to clear token locks: `GameContractsResponse`
+    GameContracts {},
+
     /// Returns top-level NFT metadata about the contract: `NFTContractInfoResponse`
     NftContractInfo {},
 }
@@ -164,3 +178,9 @@ pub enum QueryMsg {
 pub struct MinterResponse {
     pub minter: String,
 }
+
+/// Shows who can clear token locks
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct GameContractsResponse {
+    pub game_contracts: Vec<String>,
+}
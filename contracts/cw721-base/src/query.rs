@@ -10,7 +10,7 @@ use cw721::{
 };
 use cw_storage_plus::Bound;
 
-use crate::msg::{MinterResponse, QueryMsg};
+use crate::msg::{GameContractsResponse, MinterResponse, QueryMsg};
 use crate::state::{Approval, Cw721Contract, TokenInfo};
 
 const DEFAULT_LIMIT: u32 = 10;
@@ -156,9 +156,17 @@ where
         })
     }
 
+    pub fn query_game_contracts(&self, deps: Deps) -> StdResult<GameContractsResponse> {
+        let game_contracts = self.game_contracts(deps.storage)?;
+        Ok(GameContractsResponse {
+            game_contracts: game_contracts.into_iter().map(|addr| addr.to_string()).collect(),
+        })
+    }
+
     pub fn query(&self, deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         match msg {
             QueryMsg::Minter {} => to_binary(&self.minter(deps)?),
+            QueryMsg::GameContracts {} => to_binary(&self.query_game_contracts(deps)?),
             QueryMsg::ContractInfo {} => to_binary(&self.contract_info(deps)?),
             QueryMsg::NftInfo { token_id } => to_binary(&self.nft_info(deps, token_id)?),
             QueryMsg::OwnerOf {
@@ -8,6 +8,8 @@ use cosmwasm_std::{Addr, BlockInfo, StdResult, Storage};
 use cw721::{ContractInfoResponse, CustomMsg, Cw721, Expiration};
 use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
 
+use crate::extension::PlayiblePersonalization;
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct NftListing {
     pub label: String,
@@ -47,6 +49,8 @@ where
     pub contract_info: Item<'a, ContractInfoResponse>,
     pub nft_contract_info: Item<'a, NftContractInfo>,
     pub minter: Item<'a, Addr>,
+    /// Contracts allowed to clear the lock of tokens
+    pub game_contracts: Item<'a, Vec<Addr>>,
     pub token_count: Item<'a, u64>,
     /// Stored as (granter, operator) giving operator full control over granter's account
     pub operators: Map<'a, (&'a Addr, &'a Addr), Expiration>,
@@ -58,7 +62,7 @@ where
 // This is a signal, the implementations are in other files
 impl<'a, T, C> Cw721<T, C> for Cw721Contract<'a, T, C>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Serialize + DeserializeOwned + Clone + PlayiblePersonalization,
     C: CustomMsg,
 {
 }
@@ -76,6 +80,7 @@ where
             "tokens",
             "tokens__owner",
             "nft_contract_info",
+            "game_contracts",
         )
     }
 }
@@ -84,6 +89,7 @@ impl<'a, T, C> Cw721Contract<'a, T, C>
 where
     T: Serialize + DeserializeOwned + Clone,
 {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         contract_key: &'a str,
         minter_key: &'a str,
@@ -92,6 +98,7 @@ where
         tokens_key: &'a str,
         tokens_owner_key: &'a str,
         nft_contract_info_key: &'a str,
+        game_contracts_key: &'a str,
     ) -> Self {
         let indexes = TokenIndexes {
             owner: MultiIndex::new(token_owner_idx, tokens_key, tokens_owner_key),
@@ -99,6 +106,7 @@ where
         Self {
             contract_info: Item::new(contract_key),
             minter: Item::new(minter_key),
+            game_contracts: Item::new(game_contracts_key),
             token_count: Item::new(token_count_key),
             operators: Map::new(operator_key),
             tokens: IndexedMap::new(tokens_key, indexes),
@@ -117,6 +125,16 @@ where
         Ok(val)
     }
 
+    pub fn game_contracts(&self, storage: &dyn Storage) -> StdResult<Vec<Addr>> {
+        Ok(self.game_contracts.may_load(storage)?.unwrap_or_default())
+    }
+
+    pub fn decrement_tokens(&self, storage: &mut dyn Storage) -> StdResult<u64> {
+        let val = self.token_count(storage)? - 1;
+        self.token_count.save(storage, &val)?;
+        Ok(val)
+    }
+
     pub fn nft_contract_info(&self, storage: &dyn Storage) -> StdResult<NftContractInfo> {
         Ok(self
             .nft_contract_info
@@ -0,0 +1,137 @@
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, Binary, CanonicalAddr, Coin, CosmosMsg, Decimal, Deps, Event,
+    StdResult, Uint128,
+};
+use cosmwasm_storage::to_length_prefixed;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_bignumber::{Uint256, Decimal256};
+
+use crate::error::ContractError;
+use crate::helpers::{encode_msg_execute, encode_raw_query};
+use crate::querier::{deduct_tax};
+
+/// Anchor Messages
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum AnchorMsg {
+    Config {},
+    DepositStable {},
+    Receive(Cw20ReceiveMsg),
+    RedeemStable {},
+}
+
+/// Anchor Responses
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StateResponse {
+    pub total_liabilities: Decimal256,
+    pub total_reserves: Decimal256,
+    pub last_interest_updated: u64,
+    pub last_reward_updated: u64,
+    pub global_interest_index: Decimal256,
+    pub global_reward_index: Decimal256,
+    pub anc_emission_rate: Decimal256,
+    pub prev_aterra_supply: Uint256,
+    pub prev_exchange_rate: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub contract_addr: CanonicalAddr,
+    pub owner_addr: CanonicalAddr,
+    pub aterra_contract: CanonicalAddr,
+    pub interest_model: CanonicalAddr,
+    pub distribution_model: CanonicalAddr,
+    pub overseer_contract: CanonicalAddr,
+    pub collector_contract: CanonicalAddr,
+    pub distributor_contract: CanonicalAddr,
+    pub stable_denom: String,
+    pub max_borrow_factor: Decimal256,
+}
+
+/// Returns Anchor's last recorded aUST exchange rate
+pub fn query_exchange_rate(deps: Deps, anchor_addr: &Addr) -> StdResult<Decimal> {
+    let state_bin: Binary = encode_raw_query(
+        deps,
+        Binary::from(to_length_prefixed(b"state")),
+        anchor_addr.clone(),
+    )?;
+
+    let state_response: StateResponse = from_binary(&state_bin)?;
+    Ok(state_response.prev_exchange_rate.into())
+}
+
+/// Returns the anchor usd (aUST) contract address from Anchor's config
+pub fn query_aterra_contract(deps: Deps, anchor_addr: &Addr) -> StdResult<Addr> {
+    let config_bin: Binary = encode_raw_query(
+        deps,
+        Binary::from(to_length_prefixed(b"config")),
+        anchor_addr.clone(),
+    )?;
+
+    let config_response: ConfigResponse = from_binary(&config_bin)?;
+    deps.api.addr_validate(&config_response.aterra_contract.to_string())
+}
+
+/// Returns the message depositing the coin minus tax to Anchor
+pub fn deposit_msg(deps: Deps, anchor_addr: &Addr, coin: Coin) -> StdResult<CosmosMsg> {
+    let coin_deposit = deduct_tax(deps, coin)?;
+
+    encode_msg_execute(
+        to_binary(&AnchorMsg::DepositStable {})?,
+        anchor_addr.clone(),
+        vec![coin_deposit],
+    )
+}
+
+/// Returns the message sending aUST to Anchor to be redeemed for stable coins
+pub fn redeem_msg(deps: Deps, anchor_addr: &Addr, aust_amount: Uint128) -> StdResult<CosmosMsg> {
+    let aterra_contract = query_aterra_contract(deps, anchor_addr)?;
+
+    let msg = to_binary(&Cw20ExecuteMsg::Send {
+        amount: aust_amount,
+        contract: anchor_addr.to_string(),
+        msg: to_binary(&AnchorMsg::RedeemStable {})?,
+    })?;
+
+    encode_msg_execute(msg, aterra_contract, vec![])
+}
+
+/// Returns the deposited stable coins and the minted aUST from the events of a deposit
+pub fn parse_deposit_events(events: &[Event]) -> Result<(Uint128, Uint128), ContractError> {
+    Ok((
+        event_amount(events, "deposit_amount")?,
+        event_amount(events, "mint_amount")?,
+    ))
+}
+
+/// Returns the burned aUST and the stable coins received from the events of a redemption.
+/// Anchor sends the redeemed amount minus tax
+pub fn parse_redeem_events(
+    deps: Deps,
+    events: &[Event],
+    stable_denom: &str,
+) -> Result<(Uint128, Uint128), ContractError> {
+    let burn_amount = event_amount(events, "burn_amount")?;
+    let redeem_amount = event_amount(events, "redeem_amount")?;
+    let received = deduct_tax(deps, Coin {
+        denom: stable_denom.to_string(),
+        amount: redeem_amount,
+    })?;
+
+    Ok((burn_amount, received.amount))
+}
+
+// returns the amount of a wasm event attribute emitted by Anchor
+fn event_amount(events: &[Event], key: &str) -> Result<Uint128, ContractError> {
+    events
+        .iter()
+        .filter(|event| event.ty == "wasm")
+        .flat_map(|event| event.attributes.iter())
+        .find(|attr| attr.key == key)
+        .and_then(|attr| attr.value.parse::<u128>().ok())
+        .map(Uint128::from)
+        .ok_or_else(|| ContractError::InvalidAnchorReply { key: key.to_string() })
+}
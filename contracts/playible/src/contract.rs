@@ -2,39 +2,79 @@
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
     from_binary, to_binary, BankMsg, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Response,
-    StdResult, WasmQuery, WasmMsg, 
-    Coin, Uint128, Timestamp
+    StdError, StdResult, WasmQuery, WasmMsg, 
+    Addr, Coin, ContractResult, CosmosMsg, Order, Reply, SubMsg, SubMsgExecutionResponse,
+    Api, Fraction, Storage, Uint128, Timestamp
 };
-use cosmwasm_storage::to_length_prefixed;
+use cw_storage_plus::{Bound, U64Key};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
 // use cosmwasm_bignumber::{Decimal256};
 
 use cw2::set_contract_version;
-use cw20::{Cw20ExecuteMsg};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, Denom};
+use cw721::{AllNftInfoResponse, ApprovedForAllResponse, Cw721ReceiveMsg, OwnerOfResponse};
+use cw721_base::Rarity;
 
 use crate::error::ContractError;
 use crate::msg::{
-    InstantiateMsg, ExecuteMsg, QueryMsg, TokenMsg, TerrandMsg, AnchorMsg,
-    LatestRandomResponse, ConfigResponse, StateResponse, 
-    TokenExtension, NftInfoResponse
+    InstantiateMsg, ExecuteMsg, QueryMsg, TokenMsg, TerrandMsg,
+    GetRandomResponse,
+    ReceiveMsg, NftReceiveMsg, TokenExtension, NftInfoResponse, AthleteMsg, AthleteResponse,
+    AthleteSupplyResponse, RaritySupply, PackTypeResponse, SalePhasesResponse, AllocationResponse,
+    FundsResponse, YieldInfoResponse, ContestMsg, ScoreMsg, TokenIdResponse,
 };
 use crate::state::{
-    ContractInfoResponse, AthleteInfo,
-    CONTRACT_INFO, TOTAL_DEPOSIT, LAST_ROUND, 
-    total_deposit, increase_deposit,
-    athlete_list, athlete_list_read, 
+    ContractInfoResponse, AthleteInfo, AthleteCaps, AthleteMetadata, PackInfo, PackStatus, PackType, Price, SalePhase, RarityOdds, GameRules,
+    rarity_suffix,
+    CONTRACT_INFO, GAME_RULES, TOTAL_DEPOSIT, PACK_COUNT,
+    total_deposit, increase_deposit, decrease_deposit, increase_prize_pool, increase_claimable,
+    yield_shares, increase_yield_shares, decrease_yield_shares, YIELD_SHARES,
+    PRIZE_POOL, CLAIMABLE, REVENUE_SPLIT, RevenueSplit,
+    athlete_list, athlete_list_read, ATHLETE_SYMBOLS,
     ATHLETE_COUNT, athlete_count, increment_athlete_count,
+    increment_pack_count, packs, PACK_TYPES, PACK_TYPE_SALES, WALLET_PACK_SALES,
+    SALE_PHASES, PHASE_PURCHASES,
+    Contest, ContestEntry, LineupRules, CONTESTS, contest_entries, increment_contest_count, increment_entry_count,
+    decrease_prize_pool, AthleteScore, ScoreStatus, SCORES, LineupSlot, Standing, LEADERBOARDS,
+    PerRarity, LockedToken, locked_tokens, Recipe, RECIPES, PendingUpgrade, UpgradeInput, UpgradeStatus, upgrades,
+    increment_upgrade_count,
+    TOKEN_ID_SEPARATOR, token_serial, save_token_serial, rarity_from_suffix,
 };
 use crate::helpers::{
     encode_msg_execute,
-    encode_raw_query,
     encode_msg_query,
+    wrapper_msg_get_randomness,
 };
-use crate::querier::{deduct_tax};
+use crate::anchor;
+use crate::yield_source::{to_shares, YieldAction, YieldSource, DECIMAL_FRACTION};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:playible";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// drand mainnet chain parameters, the beacon relayed by terrand
+const DRAND_GENESIS_TIME: u64 = 1595431050;
+const DRAND_PERIOD: u64 = 30;
+// number of rounds a pack waits for after the round in progress at purchase time
+const DRAND_NEXT_ROUND_SECURITY: u64 = 2;
+// minimum seconds before a pack can be refunded, far longer than the wait for its round
+const MIN_REVEAL_TIMEOUT: u64 = 3600;
+// maximum number of serials checked when the token contract already holds generated IDs
+const MAX_TOKEN_ID_ATTEMPTS: u64 = 20;
+
+// reply IDs of the yield source submessages
+const DEPOSIT_REPLY_ID: u64 = 1;
+const REDEEM_REPLY_ID: u64 = 2;
+const MIGRATION_DEPOSIT_REPLY_ID: u64 = 3;
+const MIGRATION_REDEEM_REPLY_ID: u64 = 4;
+
+// maximum entry limit of a contest, so it can be settled in a single transaction
+const MAX_CONTEST_ENTRIES: u64 = 200;
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     mut deps: DepsMut,
@@ -44,31 +84,46 @@ pub fn instantiate(
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
-    let anchor_contract = deps.api.addr_validate(&msg.anchor_addr)?;
+    msg.yield_source.validate(deps.api)?;
     let terrand_contract = deps.api.addr_validate(&msg.terrand_addr)?;
     let athlete_contract = deps.api.addr_validate(&msg.athlete_addr)?;
     let marketplace_contract = deps.api.addr_validate(&msg.marketplace_addr)?;
+    let accepted_tokens = msg.accepted_tokens
+        .iter()
+        .map(|token| deps.api.addr_validate(token))
+        .collect::<StdResult<Vec<_>>>()?;
     let admin_addr = deps.api.addr_validate(&msg.admin_addr)?;
+    let scorers = msg.scorers
+        .iter()
+        .map(|scorer| deps.api.addr_validate(scorer))
+        .collect::<StdResult<Vec<_>>>()?;
+    validate_game_rules(&msg.game_rules)?;
+    validate_reveal_timeout(msg.reveal_timeout)?;
+    validate_deposit_share(msg.deposit_share)?;
+    validate_revenue_split(deps.api, &msg.revenue_split)?;
 
     let info = ContractInfoResponse {
         stable_denom: msg.stable_denom,
-        anchor_addr: anchor_contract,
+        yield_source: msg.yield_source,
         terrand_addr: terrand_contract,
         admin_addr: admin_addr,
         athlete_addr: athlete_contract,
         marketplace_addr: marketplace_contract,
-        pack_len: msg.pack_len,
-        pack_price: msg.pack_price,
-        common_cap: msg.common_cap,
-        uncommon_cap: msg.uncommon_cap,
-        rare_cap: msg.rare_cap,
-        legendary_cap: msg.legendary_cap
+        accepted_tokens,
+        reveal_timeout: msg.reveal_timeout,
+        deposit_share: msg.deposit_share,
+        scorers,
+        dispute_period: msg.dispute_period,
+        caps: msg.caps,
     };
 
     CONTRACT_INFO.save(deps.branch().storage, &info)?;
-    TOTAL_DEPOSIT.save(deps.branch().storage, &0)?;
+    GAME_RULES.save(deps.branch().storage, &msg.game_rules)?;
+    REVENUE_SPLIT.save(deps.branch().storage, &msg.revenue_split)?;
+    TOTAL_DEPOSIT.save(deps.branch().storage, &Uint128::zero())?;
+    YIELD_SHARES.save(deps.branch().storage, &Uint128::zero())?;
     ATHLETE_COUNT.save(deps.branch().storage, &0)?;
-    LAST_ROUND.save(deps.branch().storage, &0)?;
+    PACK_COUNT.save(deps.branch().storage, &0)?;
     
     Ok(Response::default())
 }
@@ -83,18 +138,70 @@ pub fn execute(
     match msg {
         ExecuteMsg::Test {} => execute_test(deps, env),
         ExecuteMsg::PurchasePack {
-            rand_seed
-        } => execute_purchase(deps, env, info, rand_seed),
+            pack_type,
+            proof,
+            referrer,
+        } => execute_purchase_native(deps, env, info, pack_type, proof, referrer),
+        ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
+        ExecuteMsg::OpenPack {
+            pack_id
+        } => execute_open_pack(deps, env, info, pack_id),
+        ExecuteMsg::RefundPack {
+            pack_id
+        } => execute_refund_pack(deps, env, info, pack_id),
         ExecuteMsg::DepositStable {} => execute_deposit(deps, env, info),
         ExecuteMsg::RedeemStable {
             amount,
         } => execute_redeem(deps, env, info, amount),
-        ExecuteMsg::Transfer {
-            amount,
-        } => execute_transfer(deps, env, info, amount),
+        ExecuteMsg::Claim {} => execute_claim(deps, env, info),
+        ExecuteMsg::UpdateRevenueSplit {
+            revenue_split
+        } => execute_update_revenue_split(deps, env, info, revenue_split),
+        ExecuteMsg::CreateContest {
+            contest
+        } => execute_create_contest(deps, env, info, contest),
+        ExecuteMsg::EnterContest {
+            contest_id,
+            token_ids,
+        } => execute_enter_contest_native(deps, env, info, contest_id, token_ids),
+        ExecuteMsg::SettleContest {
+            contest_id
+        } => execute_settle_contest(deps, env, info, contest_id),
+        ExecuteMsg::SubmitScores {
+            scores
+        } => execute_submit_scores(deps, env, info, scores),
+        ExecuteMsg::CorrectScore {
+            athlete_id,
+            game_week,
+            points,
+        } => execute_correct_score(deps, env, info, athlete_id, game_week, points),
+        ExecuteMsg::FinalizeScores {
+            athlete_ids,
+            game_week,
+        } => execute_finalize_scores(deps, env, info, athlete_ids, game_week),
+        ExecuteMsg::UpdateScorers {
+            scorers
+        } => execute_update_scorers(deps, env, info, scorers),
+        ExecuteMsg::UpdateDisputePeriod {
+            dispute_period
+        } => execute_update_dispute_period(deps, env, info, dispute_period),
         ExecuteMsg::AddAthlete {
-            symbols
-        } => execute_add_athlete(deps, env, symbols),
+            athletes
+        } => execute_add_athlete(deps, env, info, athletes),
+        ExecuteMsg::UpdateAthlete {
+            athlete_id,
+            metadata,
+        } => execute_update_athlete(deps, env, info, athlete_id, metadata),
+        ExecuteMsg::RetireAthlete {
+            athlete_id
+        } => execute_set_athlete_retired(deps, env, info, athlete_id, true),
+        ExecuteMsg::ReactivateAthlete {
+            athlete_id
+        } => execute_set_athlete_retired(deps, env, info, athlete_id, false),
+        ExecuteMsg::UpdateAthleteCaps {
+            athlete_id,
+            caps,
+        } => execute_update_athlete_caps(deps, env, info, athlete_id, caps),
         ExecuteMsg::TokenTurnover {
             new_contract
         } => execute_token_turnover(deps, env, info, new_contract),
@@ -104,26 +211,144 @@ pub fn execute(
         ExecuteMsg::UpdateMarketplace {
             new_contract
         } => execute_update_marketplace(deps, env, info, new_contract),
-        ExecuteMsg::LockToken {
-            token_id,
-            duration
-        } => execute_lock_token(deps, env, info, token_id, duration),
+        ExecuteMsg::UpdateYieldSource {
+            yield_source
+        } => execute_update_yield_source(deps, env, info, yield_source),
+        ExecuteMsg::UpdateDepositShare {
+            deposit_share
+        } => execute_update_deposit_share(deps, env, info, deposit_share),
+        ExecuteMsg::UpdateRevealTimeout {
+            reveal_timeout
+        } => execute_update_reveal_timeout(deps, env, info, reveal_timeout),
+        ExecuteMsg::UpdateAcceptedTokens {
+            tokens
+        } => execute_update_accepted_tokens(deps, env, info, tokens),
+        ExecuteMsg::AddPackType {
+            pack_type
+        } => execute_add_pack_type(deps, env, info, pack_type),
+        ExecuteMsg::UpdatePackType {
+            pack_type
+        } => execute_update_pack_type(deps, env, info, pack_type),
+        ExecuteMsg::UpdateSalePhases {
+            phases
+        } => execute_update_sale_phases(deps, env, info, phases),
+        ExecuteMsg::UpdateGameRules {
+            game_rules
+        } => execute_update_game_rules(deps, env, info, game_rules),
+        ExecuteMsg::ReceiveNft(msg) => execute_receive_nft(deps, env, info, msg),
         ExecuteMsg::UnlockToken {
             token_id,
         } => execute_unlock_token(deps, env, info, token_id),
-        ExecuteMsg::UpgradeSameToken {
-            rarity,
-            tokens,
-            athlete_id,
-        } => execute_upgrade_same_token(deps, env, info, rarity, tokens, athlete_id),
-        ExecuteMsg::UpgradeRandToken {
-            rarity,
+        ExecuteMsg::UpgradeToken {
+            recipe,
             tokens,
-            rand_seed
-        } => execute_upgrade_rand_token(deps, env, info, rarity, tokens, rand_seed),
+        } => execute_upgrade_token(deps, env, info, recipe, tokens),
+        ExecuteMsg::RevealUpgrade {
+            upgrade_id
+        } => execute_reveal_upgrade(deps, env, info, upgrade_id),
+        ExecuteMsg::RefundUpgrade {
+            upgrade_id
+        } => execute_refund_upgrade(deps, env, info, upgrade_id),
+        ExecuteMsg::AddRecipe {
+            recipe
+        } => execute_add_recipe(deps, env, info, recipe),
+        ExecuteMsg::UpdateRecipe {
+            recipe
+        } => execute_update_recipe(deps, env, info, recipe),
+        ExecuteMsg::RemoveRecipe {
+            name
+        } => execute_remove_recipe(deps, env, info, name),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        DEPOSIT_REPLY_ID => reply_deposit(deps, msg.result, true),
+        MIGRATION_DEPOSIT_REPLY_ID => reply_deposit(deps, msg.result, false),
+        REDEEM_REPLY_ID => reply_redeem(deps, msg.result),
+        MIGRATION_REDEEM_REPLY_ID => reply_migration_redeem(deps, msg.result),
+        id => Err(StdError::generic_err(format!("Unknown reply ID: {}", id)).into()),
     }
 }
 
+// records the principal deposited and the aUST minted by Anchor. A failed deposit 
+// returns an error so the whole transaction, including the pack or deposit, is rolled back
+fn reply_deposit(
+    deps: DepsMut,
+    result: ContractResult<SubMsgExecutionResponse>,
+    add_principal: bool,
+) -> Result<Response, ContractError> {
+    let events = match result {
+        ContractResult::Ok(response) => response.events,
+        ContractResult::Err(error) => return Err(ContractError::AnchorDepositFailed { error }),
+    };
+
+    let (deposit_amount, mint_amount) = anchor::parse_deposit_events(&events)?;
+    settle_deposit(deps.storage, deposit_amount, mint_amount, add_principal)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "anchor_deposit")
+        .add_attribute("deposit_amount", deposit_amount.to_string())
+        .add_attribute("aust_amount", mint_amount.to_string()))
+}
+
+// settles the aUST burned and the stable coins returned by Anchor. A failed redemption returns an error
+fn reply_redeem(
+    deps: DepsMut,
+    result: ContractResult<SubMsgExecutionResponse>,
+) -> Result<Response, ContractError> {
+    let events = match result {
+        ContractResult::Ok(response) => response.events,
+        ContractResult::Err(error) => return Err(ContractError::AnchorRedeemFailed { error }),
+    };
+
+    let stable_denom = query_contract_info(deps.as_ref())?.stable_denom;
+    let (burn_amount, received) = anchor::parse_redeem_events(deps.as_ref(), &events, &stable_denom)?;
+    let principal_amount = settle_redeem(deps.storage, burn_amount, received, stable_denom)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "anchor_redeem")
+        .add_attribute("aust_amount", burn_amount.to_string())
+        .add_attribute("principal_amount", principal_amount.to_string())
+        .add_attribute("received_amount", received.to_string()))
+}
+
+// deposits the stable coins redeemed from Anchor into the new yield source
+fn reply_migration_redeem(
+    mut deps: DepsMut,
+    result: ContractResult<SubMsgExecutionResponse>,
+) -> Result<Response, ContractError> {
+    let events = match result {
+        ContractResult::Ok(response) => response.events,
+        ContractResult::Err(error) => return Err(ContractError::AnchorRedeemFailed { error }),
+    };
+
+    let contract_info = query_contract_info(deps.as_ref())?;
+    let (burn_amount, received) = anchor::parse_redeem_events(
+        deps.as_ref(),
+        &events,
+        &contract_info.stable_denom,
+    )?;
+    decrease_yield_shares(deps.storage, burn_amount)?;
+
+    let deposit_msg = deposit_to_yield_source(
+        deps.branch(),
+        &contract_info.yield_source,
+        Coin {
+            denom: contract_info.stable_denom,
+            amount: received,
+        },
+        false,
+    )?;
+
+    Ok(Response::new()
+        .add_submessages(deposit_msg)
+        .add_attribute("action", "migrate_yield_source")
+        .add_attribute("aust_amount", burn_amount.to_string())
+        .add_attribute("received_amount", received.to_string()))
+}
+
 pub fn execute_test(
     _deps: DepsMut,
     _env: Env,
@@ -135,874 +360,4294 @@ pub fn execute_test(
     )
 }
 
+pub fn execute_purchase_native(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    pack_type: String,
+    proof: Option<Vec<String>>,
+    referrer: Option<String>,
+) -> Result<Response, ContractError> {
+    if info.funds.len() != 1 {
+        return Err(ContractError::WrongPayment { pack_type });
+    }
+
+    let payment = Price {
+        denom: Denom::Native(info.funds[0].denom.clone()),
+        amount: info.funds[0].amount,
+    };
+
+    execute_purchase(deps, env, info.sender, payment, pack_type, proof, referrer)
+}
+
+pub fn execute_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let contract_info = query_contract_info(deps.as_ref())?;
+
+    // info.sender is the token contract the tokens were sent from
+    if !contract_info.accepted_tokens.contains(&info.sender) {
+        return Err(ContractError::UnacceptedToken { token: info.sender.to_string() });
+    }
+
+    let sender = deps.api.addr_validate(&wrapper.sender)?;
+    let payment = Price {
+        denom: Denom::Cw20(info.sender),
+        amount: wrapper.amount,
+    };
+
+    match from_binary(&wrapper.msg)? {
+        ReceiveMsg::PurchasePack {
+            pack_type,
+            proof,
+            referrer,
+        } => execute_purchase(deps, env, sender, payment, pack_type, proof, referrer),
+        ReceiveMsg::EnterContest {
+            contest_id,
+            token_ids,
+        } => execute_enter_contest(deps, env, sender, Some(payment), contest_id, token_ids),
+    }
+}
+
 pub fn execute_purchase(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    payment: Price,
+    pack_type: String,
+    proof: Option<Vec<String>>,
+    referrer: Option<String>,
+) -> Result<Response, ContractError> {
+    let contract_info = query_contract_info(deps.as_ref())?;
+    let pack_type_info = PACK_TYPES.load(deps.storage, &pack_type)?;
+
+    let referrer = referrer.map(|r| deps.api.addr_validate(&r)).transpose()?;
+    if referrer.as_ref() == Some(&sender) {
+        return Err(ContractError::InvalidReferrer {});
+    }
+
+    if !pack_type_info.is_on_sale(env.block.time) {
+        return Err(ContractError::NotOnSale { pack_type });
+    }
+
+    if !pack_type_info.prices.contains(&payment) {
+        return Err(ContractError::WrongPayment { pack_type });
+    }
+
+    let sold = PACK_TYPE_SALES.may_load(deps.storage, &pack_type)?.unwrap_or_default();
+    if pack_type_info.supply.map_or(false, |supply| sold >= supply) {
+        return Err(ContractError::SoldOut { pack_type });
+    }
+
+    let wallet_sold = WALLET_PACK_SALES.may_load(deps.storage, (&pack_type, &sender))?.unwrap_or_default();
+    if pack_type_info.wallet_limit.map_or(false, |limit| wallet_sold >= limit) {
+        return Err(ContractError::WalletLimitReached { pack_type });
+    }
+
+    let sale_phase = query_active_sale_phase(deps.as_ref(), env.block.time)?;
+    if let Some(phase) = &sale_phase {
+        if let Some(merkle_root) = &phase.merkle_root {
+            let proof = proof.unwrap_or_default();
+            if !verify_merkle_proof(merkle_root, sender.as_bytes(), &proof)? {
+                return Err(ContractError::InvalidProof { phase: phase.name.clone() });
+            }
+        }
+
+        let used = PHASE_PURCHASES.may_load(deps.storage, (&phase.name, &sender))?.unwrap_or_default();
+        if phase.allocation.map_or(false, |allocation| used >= allocation) {
+            return Err(ContractError::AllocationExceeded { phase: phase.name.clone() });
+        }
+        PHASE_PURCHASES.save(deps.storage, (&phase.name, &sender), &(used + 1))?;
+    }
+
+    PACK_TYPE_SALES.save(deps.storage, &pack_type, &(sold + 1))?;
+    WALLET_PACK_SALES.save(deps.storage, (&pack_type, &sender), &(wallet_sold + 1))?;
+
+    // Bind the pack to a terrand round that has not been published yet
+    let round = next_drand_round(env.block.time);
+    let pack_id = increment_pack_count(deps.storage)?;
+
+    let pack = PackInfo {
+        pack_id,
+        pack_type: pack_type.clone(),
+        buyer: sender.clone(),
+        sale_phase: sale_phase.map(|phase| phase.name),
+        referrer,
+        round,
+        price: payment,
+        pack_len: pack_type_info.pack_len,
+        odds: pack_type_info.odds,
+        athlete_ids: pack_type_info.athlete_ids,
+        expires_at: env.block.time.plus_seconds(contract_info.reveal_timeout),
+        status: PackStatus::Pending,
+        randomness: None,
+        token_ids: vec![],
+    };
+    packs().save(deps.storage, U64Key::from(pack_id), &pack)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "purchase")
+        .add_attribute("from", &sender)
+        .add_attribute("pack_type", pack_type)
+        .add_attribute("pack_id", pack_id.to_string())
+        .add_attribute("round", round.to_string()))
+}
+
+pub fn execute_open_pack(
     mut deps: DepsMut,
     _env: Env,
-    info: MessageInfo, 
-    rand_seed: String
+    info: MessageInfo,
+    pack_id: u64,
 ) -> Result<Response, ContractError> {
-    let sender = info.sender;
-    let contract_info = query_contract_info(deps.as_ref()).unwrap();
+    let contract_info = query_contract_info(deps.as_ref())?;
+    let game_rules = query_game_rules(deps.as_ref())?;
+    let mut pack = query_pack_info(deps.as_ref(), pack_id)?;
 
-    if info.funds.len() != 1 || 
-        info.funds[0].denom != contract_info.stable_denom || 
-        info.funds[0].amount != Uint128::from(contract_info.pack_price)
-    {
-        return Err(ContractError::WrongAmount{amount:contract_info.pack_price, denom:contract_info.stable_denom})
+    if pack.status != PackStatus::Pending {
+        return Err(ContractError::PackNotPending {});
     }
-    
-    // Generate a list of mintable tokens
-    let athlete_count = query_athlete_count(deps.as_ref()).unwrap();
-    let mut mintable_token_list = vec![];
-    for n in 0..athlete_count {
-        if query_token_mintable(deps.as_ref(), n.to_string(), "C".to_string()).unwrap_or(false){    
-            // Add athlete_id to the mintable list
-            // Increment mintable tokens
-            mintable_token_list.push(n);
-        }
-    }
-
-    // Generate the list of athlete IDs to be minted
-    // let pack_len = query_contract_info(deps.as_ref()).unwrap().pack_len;
-    // let hex_list = query_terrand(deps, env, pack_len).unwrap();
-    // 
-    // let hex_list = match query_terrand(deps.branch(), env, pack_len) {
-    //     Ok(list) => list,
-    //     Err(error) => return Err(ContractError::Std(error)),
-    // };
-    // let last_round = query_last_round(deps.branch().as_ref()).unwrap();
-    
-    //let mint_index_list = [0, 0, 0, 0, 0];
-    let mint_index_list = hex_to_athlete(deps.branch().as_ref(), rand_seed.clone()).unwrap();
-    let last_round = 420;
-    let mut response = Response::new()
-        .add_attribute("action", "purchase")
-        .add_attribute("from", &sender);
-    
-    // Default NFT rarity is Common
-    for index in mint_index_list.iter() {
-        let athlete_id = mintable_token_list[*index as usize].to_string();
-        let token_id = generate_token_id(deps.branch().as_ref(), athlete_id.clone(), "C".to_string()).unwrap();
-        
-        let mint_msg = TokenMsg::Mint {
-            token_id: token_id,
-            owner: sender.clone().to_string(),
-            token_uri: None,
-            extension: TokenExtension {
-                athlete_id: athlete_id.clone(),
-                rarity: "C".to_string(),
-                is_locked: false,
-                unlock_date: None,
-                usage: 3,
-            }
-        };
 
-        response = response.add_message(WasmMsg::Execute {
-            contract_addr: contract_info.athlete_addr.to_string(),
-            msg: to_binary(&mint_msg).unwrap(),
-            funds: vec![],
-        });
+    // Only the bound round is used, any later round could be picked by whoever relays it
+    let randomness = query_terrand_round(deps.as_ref(), pack.round)?
+        .ok_or(ContractError::RoundNotAvailable { round: pack.round })?;
+
+    let mut token_ids = vec![];
+    let mut response = Response::new()
+        .add_attribute("action", "open_pack")
+        .add_attribute("from", &info.sender)
+        .add_attribute("owner", &pack.buyer)
+        .add_attribute("pack_id", pack_id.to_string())
+        .add_attribute("round", pack.round.to_string());
 
-        update_token_count(deps.branch(), athlete_id.clone(), "C".to_string())?;
+    for token in draw_pack(deps.as_ref(), &pack, &randomness)? {
+        token_ids.push(token.token_id.clone());
+        response = response.add_message(mint_token(deps.branch(), &pack.buyer, token, &game_rules)?);
+    }
 
-        /*let mint_res = encode_msg_execute(
-            to_binary(&mint_msg).unwrap(),
-            token_address.clone(),
-            vec![]
-        )?;*/
+    // The payment leaves escrow once the pack is opened, the deposit share of stable 
+    // coin payments goes to the yield source and the rest is shared between the payees
+    let deposit_amount = match &pack.price.denom {
+        Denom::Native(denom) if *denom == contract_info.stable_denom => {
+            pack.price.amount * contract_info.deposit_share
+        }
+        _ => Uint128::zero(),
+    };
 
-        // TODO: handle error from mint_res
-        // response = response.add_message(mint_res);
+    if !deposit_amount.is_zero() {
+        let deposit_msg = deposit_to_yield_source(
+            deps.branch(),
+            &contract_info.yield_source,
+            Coin {
+                denom: contract_info.stable_denom.clone(),
+                amount: deposit_amount,
+            },
+            true,
+        )?;
+        response = response
+            .add_submessages(deposit_msg)
+            .add_attribute("deposit_amount", deposit_amount.to_string());
     }
-    response = response.add_attribute("last_round", last_round.to_string());
-    
+
+    let revenue_split = REVENUE_SPLIT.load(deps.storage)?;
+    distribute_revenue(
+        deps.storage,
+        &revenue_split,
+        &Price {
+            denom: pack.price.denom.clone(),
+            amount: pack.price.amount.checked_sub(deposit_amount).map_err(StdError::from)?,
+        },
+        pack.referrer.as_ref(),
+    )?;
+
+    // Keep a record of the randomness used by the pack so the draw can be audited
+    pack.status = PackStatus::Opened;
+    pack.randomness = Some(randomness);
+    pack.token_ids = token_ids;
+    packs().save(deps.storage, U64Key::from(pack_id), &pack)?;
+
     Ok(response)
 }
 
+pub fn execute_refund_pack(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    pack_id: u64,
+) -> Result<Response, ContractError> {
+    let mut pack = query_pack_info(deps.as_ref(), pack_id)?;
+
+    if info.sender != pack.buyer {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if pack.status != PackStatus::Pending {
+        return Err(ContractError::PackNotPending {});
+    }
+
+    // A pack that cannot be opened, e.g. once its athletes have been capped or retired
+    // since the purchase, is refunded right away so the payment does not stay in escrow
+    let blocked = match query_terrand_round(deps.as_ref(), pack.round)? {
+        Some(randomness) => is_draw_blocked(draw_pack(deps.as_ref(), &pack, &randomness))?,
+        None => false,
+    };
+
+    if !blocked {
+        if env.block.time < pack.expires_at {
+            return Err(ContractError::PackNotExpired {});
+        }
+
+        // Once terrand holds the round or a later one, the round can be relayed from the drand
+        // history and the pack opened, so the buyer cannot back out of a draw they can compute
+        if query_latest_round(deps.as_ref())? >= pack.round {
+            return Err(ContractError::RoundPublished { round: pack.round });
+        }
+    }
+
+    pack.status = PackStatus::Refunded;
+    packs().save(deps.storage, U64Key::from(pack_id), &pack)?;
+
+    // Refunded packs go back on sale
+    PACK_TYPE_SALES.update::<_, StdError>(deps.storage, &pack.pack_type, |sold| {
+        Ok(sold.unwrap_or_default().saturating_sub(1))
+    })?;
+    WALLET_PACK_SALES.update::<_, StdError>(deps.storage, (&pack.pack_type, &pack.buyer), |sold| {
+        Ok(sold.unwrap_or_default().saturating_sub(1))
+    })?;
+    if let Some(phase) = &pack.sale_phase {
+        PHASE_PURCHASES.update::<_, StdError>(deps.storage, (phase, &pack.buyer), |used| {
+            Ok(used.unwrap_or_default().saturating_sub(1))
+        })?;
+    }
+
+    Ok(Response::new()
+        .add_message(payment_msg(&pack.buyer, &pack.price)?)
+        .add_attribute("action", "refund_pack")
+        .add_attribute("to", &pack.buyer)
+        .add_attribute("pack_id", pack_id.to_string())
+        .add_attribute("amount", pack.price.to_string()))
+}
+
 pub fn execute_deposit(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
 ) -> Result<Response, ContractError> {
     let sender = info.sender;
+    let contract_info = query_contract_info(deps.as_ref())?;
 
     let deposit_amount: Uint128 = info
         .funds
         .iter()
-        .find(|c| c.denom == "uusd")
-        .map(|c| Uint128::from(c.amount))
+        .find(|c| c.denom == contract_info.stable_denom)
+        .map(|c| c.amount)
         .unwrap_or_else(Uint128::zero);
-    
-    // coin deposit minus tax
-    let coin_deposit = deduct_tax(
-        deps.as_ref(), 
+
+    let deposit_msg = deposit_to_yield_source(
+        deps,
+        &contract_info.yield_source,
         Coin {
-            denom: "uusd".to_string(),
+            denom: contract_info.stable_denom.clone(),
             amount: deposit_amount
-        }
-    )?;
-
-    let anchor_contract = query_contract_info(deps.as_ref()).unwrap().anchor_addr;
-    
-    // execute anchor's deposit stable contract
-    let deposit_msg = to_binary(&AnchorMsg::DepositStable{})?;
-    let anchor_response = encode_msg_execute(
-        deposit_msg,
-        anchor_contract.clone(),
-        vec![coin_deposit.clone()]
+        },
+        true,
     )?;
 
-    increase_deposit(deps.storage, coin_deposit.amount.u128() as u64)?;
-    
     Ok(Response::new()
-        .add_message(anchor_response)
+        .add_submessages(deposit_msg)
         .add_attribute("action", "deposit")
         .add_attribute("from", &sender)
-        .add_attribute("to", &anchor_contract)
-        .add_attribute("deposit_amount", &coin_deposit.amount.to_string()))
+        .add_attribute("deposit_amount", &deposit_amount.to_string()))
 }
 
-pub fn execute_redeem(
+// deposits the coin into the yield source. Sources settling right away are recorded now, 
+// otherwise the returned submessage is recorded once it replies
+fn deposit_to_yield_source(
     deps: DepsMut,
-    _env: Env,
-    info: MessageInfo,
-    amount: Uint128,
-) -> Result<Response, ContractError> {
-    let sender = info.sender;
-    let anchor_contract = query_contract_info(deps.as_ref()).unwrap().anchor_addr;
-
-     // get exchange rate from anchor state
-     let state_bin: Binary = encode_raw_query(
-        deps.as_ref(), 
-        Binary::from(to_length_prefixed(b"state")),
-        anchor_contract.clone(),
-    )?;
+    yield_source: &YieldSource,
+    coin: Coin,
+    add_principal: bool,
+) -> StdResult<Vec<SubMsg>> {
+    let reply_id = if add_principal { DEPOSIT_REPLY_ID } else { MIGRATION_DEPOSIT_REPLY_ID };
 
-    //TODO: Declare this as a static variable, which is incredibly difficult to do for some reason
-    let decimal_fraction: Uint128 = Uint128::from(1_000_000_000_000_000_000 as u128);
+    match yield_source.deposit(deps.as_ref(), coin)? {
+        YieldAction::Pending(msg) => Ok(vec![SubMsg::reply_always(msg, reply_id)]),
+        YieldAction::Settled { amount, shares } => {
+            settle_deposit(deps.storage, amount, shares, add_principal)?;
+            Ok(vec![])
+        }
+    }
+}
 
-    // transform binary response to state response
-    let state_response: StateResponse = from_binary(&state_bin)?;
-    let exchange_rate: Decimal = state_response.prev_exchange_rate.into();
+// records the shares received for a deposit, migrated deposits are already part of the principal
+fn settle_deposit(
+    storage: &mut dyn Storage,
+    amount: Uint128,
+    shares: Uint128,
+    add_principal: bool,
+) -> StdResult<()> {
+    if add_principal {
+        increase_deposit(storage, amount)?;
+    }
+    increase_yield_shares(storage, shares)?;
 
-    let contract_msg = to_binary(&AnchorMsg::RedeemStable{})?;
-    let aust_amount = amount.multiply_ratio(
-        decimal_fraction,
-        decimal_fraction * exchange_rate + decimal_fraction
-    );
+    Ok(())
+}
 
-    // get anchor usd (aust) contract address from anchor config
-    let config_bin: Binary = encode_raw_query(
-        deps.as_ref(),
-        Binary::from(to_length_prefixed(b"config")),
-        anchor_contract.clone(),
-    )?;
+// reduces the principal by the share of the shares redeemed. The stable coins received up
+// to that principal go back to the operating funds of the treasury and only the yield on top
+// of it goes to the prize pool. Returns the principal redeemed
+fn settle_redeem(
+    storage: &mut dyn Storage,
+    shares: Uint128,
+    received: Uint128,
+    stable_denom: String,
+) -> Result<Uint128, ContractError> {
+    let shares_held = yield_shares(storage)?;
+    if shares > shares_held {
+        return Err(ContractError::InsufficientDeposit {});
+    }
 
-    // transform binary response to state response
-    let config_response: ConfigResponse = from_binary(&config_bin)?;
-    let aterra_contract = deps.api.addr_validate(&config_response.aterra_contract.to_string())?;
+    let principal_amount = total_deposit(storage)?.multiply_ratio(shares, shares_held);
+    decrease_deposit(storage, principal_amount)?;
+    decrease_yield_shares(storage, shares)?;
 
-    // create a send message
-    let msg = to_binary(&Cw20ExecuteMsg::Send{
-        amount: aust_amount,
-        contract: anchor_contract.to_string(),
-        msg: contract_msg
+    // A redemption worth less than its principal, e.g. after taxes, has no yield
+    let yield_amount = received.saturating_sub(principal_amount);
+    let treasury = REVENUE_SPLIT.load(storage)?.treasury.address;
+    increase_claimable(storage, &treasury, &Price {
+        denom: Denom::Native(stable_denom.clone()),
+        amount: received - yield_amount,
     })?;
+    if !yield_amount.is_zero() {
+        increase_prize_pool(storage, &Price {
+            denom: Denom::Native(stable_denom),
+            amount: yield_amount,
+        })?;
+    }
 
-    let anchor_response = encode_msg_execute(
-        msg,
-        aterra_contract,
-        vec![]
-    )?;
-
-    Ok(Response::new()
-        .add_message(anchor_response)
-        .add_attribute("action", "receive")
-        .add_attribute("from", &sender)
-        .add_attribute("to", &anchor_contract)
-        .add_attribute("amount", &amount.to_string())
-        .add_attribute("aust_amount", &aust_amount.to_string()))
+    Ok(principal_amount)
 }
 
-pub fn execute_transfer(
+pub fn execute_redeem(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
     amount: Uint128,
 ) -> Result<Response, ContractError> {
-    let admin_addr = query_contract_info(deps.as_ref()).unwrap().admin_addr;
+    let sender = info.sender;
+    let contract_info = query_contract_info(deps.as_ref())?;
 
-    if info.sender != admin_addr {
+    if sender != contract_info.admin_addr {
         return Err(ContractError::Unauthorized{})
     }
 
-    Ok(Response::new()
-        .add_message(BankMsg::Send {
-            to_address: info.sender.to_string(),
-            amount: vec![
-                Coin {
-                    denom: "uusd".to_string(),
-                    amount: amount
-                }
-            ],
-        })
-        .add_attribute("action", "transfer")
-        .add_attribute("amount", &amount.to_string())
-    )
-}
-
-pub fn execute_add_athlete(
-    deps: DepsMut,
-    _env: Env,
-    symbols: Vec<String>,
-) -> Result<Response, ContractError> {
-    let athlete_id = query_athlete_count(deps.as_ref()).unwrap();
-    let mut response = Response::new()
-        .add_attribute("action", "add_tokens");
+    let exchange_rate = contract_info.yield_source.exchange_rate(deps.as_ref())?;
+    let shares = to_shares(amount, exchange_rate);
 
-    for symbol in symbols {
-        let athlete_info = AthleteInfo {
-            symbol: symbol.clone(),
-            common_count: 0,
-            uncommon_count: 0,
-            rare_count: 0,
-            legendary_count: 0,
-        };
+    if shares > yield_shares(deps.storage)? {
+        return Err(ContractError::InsufficientDeposit {});
+    }
 
-        athlete_list(deps.storage).update::<_, ContractError>(&athlete_id.to_string().as_bytes(), |old| match old {
-            Some(_) => Err(ContractError::Claimed {}),
-            None => Ok(athlete_info.clone()),
-        })?;
-        
-        increment_athlete_count(deps.storage)?;
+    let mut response = Response::new()
+        .add_attribute("action", "redeem")
+        .add_attribute("from", &sender)
+        .add_attribute("amount", &amount.to_string())
+        .add_attribute("shares", &shares.to_string());
 
-        response = response.add_attribute("symbol", symbol.clone());
+    match contract_info.yield_source.redeem(deps.as_ref(), shares)? {
+        // The principal and prize pool are updated from the Anchor events once it replies
+        YieldAction::Pending(msg) => {
+            response = response.add_submessage(SubMsg::reply_always(msg, REDEEM_REPLY_ID));
+        }
+        YieldAction::Settled { amount, shares } => {
+            let principal_amount = settle_redeem(deps.storage, shares, amount, contract_info.stable_denom)?;
+            response = response.add_attribute("principal_amount", principal_amount.to_string());
+        }
     }
-    
+
     Ok(response)
 }
 
-pub fn execute_token_turnover(
+pub fn execute_update_yield_source(
     mut deps: DepsMut,
-    env: Env,
+    _env: Env,
     info: MessageInfo,
-    new_contract: String,
+    yield_source: YieldSource,
 ) -> Result<Response, ContractError> {
-    let new_address = deps.api.addr_validate(&new_contract)?;
-    let contract_info = query_contract_info(deps.branch().as_ref()).unwrap();
+    let contract_info = query_contract_info(deps.as_ref())?;
 
     if info.sender != contract_info.admin_addr {
         return Err(ContractError::Unauthorized{})
     }
 
-    let mut response = Response::new()
-        .add_attribute("action", "token_turnover")
-        .add_attribute("from", &env.contract.address)
-        .add_attribute("to", &new_address.to_string());
+    yield_source.validate(deps.api)?;
 
-    let update_msg = TokenMsg::UpdateMinter {
-        minter: new_address.clone().to_string(),
+    let old_source = contract_info.yield_source.clone();
+    let stable_denom = contract_info.stable_denom.clone();
+    let info = ContractInfoResponse {
+        yield_source: yield_source.clone(),
+        ..contract_info
     };
+    CONTRACT_INFO.save(deps.storage, &info)?;
 
-    let token_res = encode_msg_execute(
-        to_binary(&update_msg).unwrap(),
-        contract_info.athlete_addr.clone(),
-        vec![]
-    )?;
+    let mut response = Response::new()
+        .add_attribute("action", "update_yield_source");
 
-    // TODO: handle error from token_res
-    response = response.add_message(token_res);
+    let shares = yield_shares(deps.storage)?;
+    if shares.is_zero() || !old_source.requires_migration(&yield_source) {
+        return Ok(response);
+    }
+
+    // Redeem everything from the old source and deposit it into the new one, 
+    // the principal is kept so the yield earned so far stays reported as yield
+    match old_source.redeem(deps.as_ref(), shares)? {
+        YieldAction::Pending(msg) => {
+            response = response.add_submessage(SubMsg::reply_always(msg, MIGRATION_REDEEM_REPLY_ID));
+        }
+        YieldAction::Settled { amount, shares } => {
+            decrease_yield_shares(deps.storage, shares)?;
+            let deposit_msg = deposit_to_yield_source(
+                deps.branch(),
+                &yield_source,
+                Coin {
+                    denom: stable_denom,
+                    amount,
+                },
+                false,
+            )?;
+            response = response
+                .add_submessages(deposit_msg)
+                .add_attribute("migrated_amount", amount.to_string());
+        }
+    }
 
     Ok(response)
 }
 
-pub fn execute_update_cw721(
-    mut deps: DepsMut,
+pub fn execute_claim(
+    deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    new_contract: String,
 ) -> Result<Response, ContractError> {
-    let contract_info = query_contract_info(deps.as_ref()).unwrap();
-    let new_address = deps.api.addr_validate(&new_contract)?;
-    let old_address = contract_info.athlete_addr;
+    let balances = query_claimable(deps.as_ref(), info.sender.to_string())?;
 
-    if info.sender != contract_info.admin_addr.clone() {
-        return Err(ContractError::Unauthorized{})
+    if balances.is_empty() {
+        return Err(ContractError::NothingToClaim {});
     }
 
-    let info = ContractInfoResponse {
-        stable_denom: contract_info.stable_denom,
-        anchor_addr: contract_info.anchor_addr,
-        terrand_addr: contract_info.terrand_addr,
-        admin_addr: contract_info.admin_addr.clone(),
-        athlete_addr: new_address.clone(),
-        marketplace_addr: contract_info.marketplace_addr,
-        pack_len: contract_info.pack_len,
-        pack_price: contract_info.pack_price,
-        common_cap: contract_info.common_cap,
-        uncommon_cap: contract_info.uncommon_cap,
-        rare_cap: contract_info.rare_cap,
-        legendary_cap: contract_info.legendary_cap
-    };
+    let mut response = Response::new()
+        .add_attribute("action", "claim")
+        .add_attribute("to", &info.sender);
 
-    CONTRACT_INFO.save(deps.branch().storage, &info)?;
+    for balance in balances {
+        CLAIMABLE.remove(deps.storage, (&info.sender, &balance.denom_name()));
+        response = response
+            .add_message(payment_msg(&info.sender, &balance)?)
+            .add_attribute("amount", balance.to_string());
+    }
 
-    Ok(Response::new()
-        .add_attribute("action", "update_cw721")
-        .add_attribute("from", old_address.clone())
-        .add_attribute("to", new_address.clone()))
+    Ok(response)
 }
 
-pub fn execute_update_marketplace(
-    mut deps: DepsMut,
+pub fn execute_update_revenue_split(
+    deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    new_contract: String,
+    revenue_split: RevenueSplit,
 ) -> Result<Response, ContractError> {
-    let contract_info = query_contract_info(deps.as_ref()).unwrap();
-    let new_address = deps.api.addr_validate(&new_contract)?;
-    let old_address = contract_info.marketplace_addr;
+    let admin_addr = query_contract_info(deps.as_ref())?.admin_addr;
 
-    if info.sender != contract_info.admin_addr.clone() {
+    if info.sender != admin_addr {
         return Err(ContractError::Unauthorized{})
     }
 
-    let info = ContractInfoResponse {
-        stable_denom: contract_info.stable_denom,
-        anchor_addr: contract_info.anchor_addr,
-        terrand_addr: contract_info.terrand_addr,
-        admin_addr: contract_info.admin_addr.clone(),
-        athlete_addr: contract_info.athlete_addr,
-        marketplace_addr: new_address.clone(),
-        pack_len: contract_info.pack_len,
-        pack_price: contract_info.pack_price,
-        common_cap: contract_info.common_cap,
-        uncommon_cap: contract_info.uncommon_cap,
-        rare_cap: contract_info.rare_cap,
-        legendary_cap: contract_info.legendary_cap
-    };
-
-    CONTRACT_INFO.save(deps.branch().storage, &info)?;
+    validate_revenue_split(deps.api, &revenue_split)?;
+    REVENUE_SPLIT.save(deps.storage, &revenue_split)?;
 
     Ok(Response::new()
-        .add_attribute("action", "update_marketplace")
-        .add_attribute("from", old_address.clone())
-        .add_attribute("to", new_address.clone()))
+        .add_attribute("action", "update_revenue_split"))
 }
 
-pub fn execute_lock_token(
-    mut deps: DepsMut,
-    env: Env,
-    _info: MessageInfo,
-    token_id: String,
-    duration: String
-) -> Result<Response, ContractError> {
-    let token_address = query_contract_info(deps.branch().as_ref()).unwrap().athlete_addr;
-    let mut token = query_token_info(deps.as_ref(), token_id.clone()).unwrap();
+// shares the revenue between the claimable balances of the payees and the prize pool. 
+// The treasury gets the referrer share when there is no referrer and the rounding leftovers
+fn distribute_revenue(
+    storage: &mut dyn Storage,
+    revenue_split: &RevenueSplit,
+    revenue: &Price,
+    referrer: Option<&Addr>,
+) -> StdResult<()> {
+    let total_weight = revenue_split.total_weight();
+    let share = |weight: u64| Price {
+        denom: revenue.denom.clone(),
+        amount: revenue.amount.multiply_ratio(weight, total_weight),
+    };
+    let mut distributed = Uint128::zero();
 
-    // TODO: Add token ownership authentication when executing this function
-    // Use AllNftInfoResponse instead of NftInfoResponse from CW721 package
-    // if info.sender != token.owner {
-    //     return Err(ContractError::Unauthorized {});
-    // }
+    let prize_pool = share(revenue_split.prize_pool_weight);
+    increase_prize_pool(storage, &prize_pool)?;
+    distributed = distributed.checked_add(prize_pool.amount)?;
 
-    if !query_use_token(deps.as_ref(), token_id.clone())?{
-        return Err(ContractError::UsageCapped {});
+    for payee in revenue_split.royalties.iter() {
+        let royalty = share(payee.weight);
+        increase_claimable(storage, &payee.address, &royalty)?;
+        distributed = distributed.checked_add(royalty.amount)?;
     }
 
-    let curr_date = env.block.time;
-    if duration.clone().eq("hour"){
-        token.extension.unlock_date = Some(curr_date.plus_seconds(3_600));
-    } else if duration.clone().eq("day"){
-        token.extension.unlock_date = Some(curr_date.plus_seconds(86_400));
-    } else if duration.clone().eq("week"){
-        token.extension.unlock_date = Some(curr_date.plus_seconds(604_800));
-    } else {
-        token.extension.unlock_date = Some(curr_date.plus_seconds(60));
+    if let Some(referrer) = referrer {
+        let referral = share(revenue_split.referrer_weight);
+        increase_claimable(storage, referrer, &referral)?;
+        distributed = distributed.checked_add(referral.amount)?;
     }
 
-    let update_msg = TokenMsg::UpdateToken {
-        token_id: token_id.clone(),
-        token_uri: token.token_uri,
-        extension: TokenExtension {
-            athlete_id: token.extension.athlete_id,
-            rarity: token.extension.rarity,
-            is_locked: true,
-            unlock_date: token.extension.unlock_date,
-            usage: token.extension.usage - 1
-        }
+    increase_claimable(storage, &revenue_split.treasury.address, &Price {
+        denom: revenue.denom.clone(),
+        amount: revenue.amount.checked_sub(distributed)?,
+    })?;
+
+    Ok(())
+}
+
+// checks the upgrade fee sent with an upgrade, returning the paid fee
+fn check_upgrade_fee(
+    recipe: &str,
+    funds: &[Coin],
+    fee: Option<&Coin>,
+) -> Result<Option<Coin>, ContractError> {
+    // Funds sent to a recipe without a fee would be stuck in the contract
+    let fee = match fee {
+        Some(fee) => fee,
+        None if funds.is_empty() => return Ok(None),
+        None => return Err(ContractError::NoUpgradeFee { recipe: recipe.to_string() }),
     };
-    
-    Ok(Response::new()
-        .add_message(WasmMsg::Execute {
-            contract_addr: token_address.to_string(),
-            msg: to_binary(&update_msg).unwrap(),
-            funds: vec![],
-        })
-        .add_attribute("action", "lock_token")
-        .add_attribute("token_id", token_id.clone())
-        .add_attribute("duration", duration.clone()))
+
+    if funds.iter().any(|coin| coin.denom != fee.denom) {
+        return Err(ContractError::WrongUpgradeDenom { denom: fee.denom.clone() });
+    }
+
+    if funds.len() != 1 || funds[0] != *fee {
+        return Err(ContractError::WrongUpgradeFee { fee: fee.to_string() });
+    }
+
+    Ok(Some(fee.clone()))
 }
 
-pub fn execute_unlock_token(
+// shares a paid upgrade fee between the payees
+fn distribute_upgrade_fee(
+    storage: &mut dyn Storage,
+    fee: &Coin,
+) -> Result<(), ContractError> {
+    let revenue_split = REVENUE_SPLIT.load(storage)?;
+    distribute_revenue(
+        storage,
+        &revenue_split,
+        &Price {
+            denom: Denom::Native(fee.denom.clone()),
+            amount: fee.amount,
+        },
+        None,
+    )?;
+
+    Ok(())
+}
+
+pub fn execute_create_contest(
     mut deps: DepsMut,
-    env: Env,
-    _info: MessageInfo,
-    token_id: String,
+    _env: Env,
+    info: MessageInfo,
+    contest: ContestMsg,
 ) -> Result<Response, ContractError> {
-    let token_address = query_contract_info(deps.branch().as_ref()).unwrap().athlete_addr;
-    let token = query_token_info(deps.as_ref(), token_id.clone()).unwrap();
+    let admin_addr = query_contract_info(deps.as_ref())?.admin_addr;
+
+    if info.sender != admin_addr {
+        return Err(ContractError::Unauthorized{})
+    }
 
-    // TODO: Add token ownership authentication when executing this function
-    // Use AllNftInfoResponse instead of NftInfoResponse from CW721 package
-    // if info.sender != token.owner {
-    //     return Err(ContractError::Unauthorized {});
-    // }
+    validate_contest(deps.as_ref(), &contest)?;
 
-    if !query_unlock_token(deps.as_ref(), env, token_id.clone())?{
-        return Err(ContractError::Locked {});
+    // Guaranteed prizes are reserved from the prize pool, entry fees are added on top
+    if let Some(prize_pool) = &contest.prize_pool {
+        decrease_prize_pool(deps.branch().storage, prize_pool)
+            .map_err(|_| ContractError::InsufficientPrizePool {})?;
     }
+    let prize_pool = contest.prize_pool.clone().or_else(|| contest.entry_fee.as_ref().map(|fee| Price {
+        denom: fee.denom.clone(),
+        amount: Uint128::zero(),
+    }));
+
+    let contest_id = increment_contest_count(deps.storage)?;
+    CONTESTS.save(deps.storage, U64Key::from(contest_id), &Contest {
+        contest_id,
+        name: contest.name.clone(),
+        entry_start: contest.entry_start,
+        entry_end: contest.entry_end,
+        end_time: contest.end_time,
+        game_week: contest.game_week,
+        lineup_size: contest.lineup_size,
+        rules: contest.rules,
+        entry_fee: contest.entry_fee,
+        prize_pool,
+        payouts: contest.payouts,
+        max_entries: contest.max_entries,
+        entries: 0,
+        settled: false,
+    })?;
 
-    let update_msg = TokenMsg::UpdateToken {
-        token_id: token_id.clone(),
-        token_uri: token.token_uri,
-        extension: TokenExtension {
-            athlete_id: token.extension.athlete_id,
-            rarity: token.extension.rarity,
-            is_locked: false,
-            unlock_date: None,
-            usage: token.extension.usage
-        }
-    };
-    
     Ok(Response::new()
-        .add_message(WasmMsg::Execute {
-            contract_addr: token_address.to_string(),
-            msg: to_binary(&update_msg).unwrap(),
-            funds: vec![],
-        })
-        .add_attribute("action", "unlock_token")
-        .add_attribute("token_id", token_id.clone()))
+        .add_attribute("action", "create_contest")
+        .add_attribute("contest_id", contest_id.to_string())
+        .add_attribute("name", contest.name))
 }
 
-pub fn execute_upgrade_same_token(
-    mut deps: DepsMut,
+pub fn execute_enter_contest_native(
+    deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    rarity: String,
-    tokens: Vec<String>,
-    athlete_id: String, 
+    contest_id: u64,
+    token_ids: Vec<String>,
 ) -> Result<Response, ContractError> {
-    let athlete_contract = query_contract_info(deps.branch().as_ref()).unwrap().athlete_addr;
-    let token_id = generate_token_id(deps.branch().as_ref(), athlete_id.clone(), rarity.clone()).unwrap();
-    let sender = info.sender;
+    let payment = match info.funds.len() {
+        0 => None,
+        1 => Some(Price {
+            denom: Denom::Native(info.funds[0].denom.clone()),
+            amount: info.funds[0].amount,
+        }),
+        _ => return Err(ContractError::WrongEntryFee { contest_id }),
+    };
 
-    if !query_token_mintable(deps.branch().as_ref(), athlete_id.clone(), rarity.clone())?{    
-        return Err(ContractError::Capped {});
+    execute_enter_contest(deps, env, info.sender, payment, contest_id, token_ids)
+}
+
+pub fn execute_enter_contest(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    payment: Option<Price>,
+    contest_id: u64,
+    token_ids: Vec<String>,
+) -> Result<Response, ContractError> {
+    let athlete_addr = query_contract_info(deps.as_ref())?.athlete_addr;
+    let mut contest = CONTESTS.load(deps.storage, U64Key::from(contest_id))?;
+
+    if !contest.is_open(env.block.time) {
+        return Err(ContractError::ContestNotOpen { contest_id });
+    }
+
+    if contest.entries >= contest.max_entries {
+        return Err(ContractError::ContestFull { contest_id });
+    }
+
+    if payment != contest.entry_fee {
+        return Err(ContractError::WrongEntryFee { contest_id });
+    }
+
+    if token_ids.len() != contest.lineup_size as usize {
+        return Err(ContractError::InvalidLineupSize { expected: contest.lineup_size });
     }
 
+    let entry_id = increment_entry_count(deps.storage)?;
     let mut response = Response::new()
-        .add_attribute("action", "upgrade_same_token")
-        .add_attribute("from", sender.clone());
+        .add_attribute("action", "enter_contest")
+        .add_attribute("from", &sender)
+        .add_attribute("contest_id", contest_id.to_string())
+        .add_attribute("entry_id", entry_id.to_string());
 
-    // Burn fodder tokens
-    for token in tokens.iter() {
-        let burn_msg = TokenMsg::TransferNft {
-            recipient: env.contract.address.to_string(),
-            token_id: token.clone()
+    let mut tokens = vec![];
+    for (i, token_id) in token_ids.iter().enumerate() {
+        if token_ids[..i].contains(token_id) {
+            return Err(ContractError::DuplicateToken { token_id: token_id.clone() });
+        }
+
+        if query_token_owner(deps.as_ref(), token_id.clone())? != sender {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        let token = query_token_info(deps.as_ref(), token_id.clone())?;
+        // Contest locks lapse once their unlock date has passed
+        if token.extension.is_locked &&
+            token.extension.unlock_date.map_or(true, |unlock_date| env.block.time < unlock_date)
+        {
+            return Err(ContractError::TokenInUse { token_id: token_id.clone() });
+        }
+        if token.extension.usage == 0 {
+            return Err(ContractError::UsageCapped {});
+        }
+        tokens.push(token);
+    }
+
+    let extensions: Vec<TokenExtension> = tokens.iter().map(|token| token.extension.clone()).collect();
+    validate_lineup(deps.as_ref(), &contest.rules, &extensions)?;
+
+    let lineup = token_ids
+        .iter()
+        .zip(extensions)
+        .map(|(token_id, extension)| LineupSlot {
+            token_id: token_id.clone(),
+            athlete_id: extension.athlete_id,
+            rarity: extension.rarity,
+        })
+        .collect();
+
+    // Lock each token of the lineup until the contest ends
+    for (token_id, token) in token_ids.iter().zip(tokens) {
+        let update_msg = TokenMsg::UpdateToken {
+            token_id: token_id.clone(),
+            token_uri: token.token_uri,
+            extension: TokenExtension {
+                is_locked: true,
+                unlock_date: Some(contest.end_time),
+                usage: token.extension.usage - 1,
+                ..token.extension
+            }
         };
 
         response = response.add_message(WasmMsg::Execute {
-            contract_addr: athlete_contract.clone().to_string(),
-            msg: to_binary(&burn_msg).unwrap(),
+            contract_addr: athlete_addr.to_string(),
+            msg: to_binary(&update_msg)?,
             funds: vec![],
         });
     }
 
-    let mut usage_cap = 3;
-    if rarity.eq("U"){
-        usage_cap = 5;
-    } else if rarity.eq("R"){
-        usage_cap = 10;
-    } else if rarity.eq("L"){
-        usage_cap = 999_999_999;
+    if let (Some(prize_pool), Some(fee)) = (contest.prize_pool.as_mut(), &payment) {
+        prize_pool.amount = prize_pool.amount.checked_add(fee.amount).map_err(StdError::from)?;
     }
-    
-    // Mint higher rarity token
-    let mint_msg = TokenMsg::Mint {
-        token_id: token_id,
-        owner: sender.clone().to_string(),
-        token_uri: None,
-        extension: TokenExtension {
-            athlete_id: athlete_id.clone(),
-            rarity: rarity.clone(),
-            is_locked: false,
-            unlock_date: None,
-            usage: usage_cap,
-        }
-    };
+    contest.entries += 1;
+    CONTESTS.save(deps.storage, U64Key::from(contest_id), &contest)?;
 
-    response = response.add_message(WasmMsg::Execute {
-        contract_addr: athlete_contract.clone().to_string(),
-        msg: to_binary(&mint_msg).unwrap(),
-        funds: vec![],
-    });
+    contest_entries().save(deps.storage, U64Key::from(entry_id), &ContestEntry {
+        entry_id,
+        contest_id,
+        owner: sender,
+        lineup,
+    })?;
 
-    update_token_count(deps.branch(), athlete_id.clone(), rarity.clone())?;
-    
     Ok(response)
 }
 
-pub fn execute_upgrade_rand_token(
-    mut deps: DepsMut,
+pub fn execute_settle_contest(
+    deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    rarity: String,
-    tokens: Vec<String>,
-    rand_seed: String
+    contest_id: u64,
 ) -> Result<Response, ContractError> {
-    let athlete_contract = query_contract_info(deps.branch().as_ref()).unwrap().athlete_addr;
-    let sender = info.sender;
+    let admin_addr = query_contract_info(deps.as_ref())?.admin_addr;
 
-    let mut response = Response::new()
-        .add_attribute("action", "upgrade_rand_token")
-        .add_attribute("from", sender.clone());
-    
-    // Burn fodder tokens
-    for token in tokens {
-        let burn_msg = TokenMsg::TransferNft {
-            recipient: env.contract.address.to_string(),
-            token_id: token.clone()
-        };
+    if info.sender != admin_addr {
+        return Err(ContractError::Unauthorized{})
+    }
 
-        response = response.add_message(WasmMsg::Execute {
-            contract_addr: athlete_contract.to_string(),
-            msg: to_binary(&burn_msg).unwrap(),
-            funds: vec![],
-        });
+    let mut contest = CONTESTS.load(deps.storage, U64Key::from(contest_id))?;
+    if contest.settled {
+        return Err(ContractError::ContestSettled { contest_id });
+    }
+    if env.block.time < contest.end_time {
+        return Err(ContractError::ContestNotEnded { contest_id });
     }
 
-    // Generate a list of mintable tokens
-    let athlete_count = query_athlete_count(deps.as_ref()).unwrap();
-    let mut mintable_list = vec![];
-    for n in 0..athlete_count {
-        if query_token_mintable(deps.as_ref(), n.to_string(), rarity.clone()).unwrap_or(false){    
-            // Add athlete_id to the mintable list
-            // Increment mintable tokens
-            mintable_list.push(n);
+    let game_rules = query_game_rules(deps.as_ref())?;
+    let entries = contest_entries()
+        .idx
+        .contest
+        .prefix(U64Key::from(contest_id))
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, entry)| entry))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    // Every lineup athlete needs a final score, athletes who did not play are scored zero by
+    // the oracle, so a contest cannot be settled before all the scores are in
+    let mut points = BTreeMap::new();
+    let mut scored = vec![];
+    for entry in entries {
+        let mut score = Decimal::zero();
+        for slot in entry.lineup.iter() {
+            if !points.contains_key(&slot.athlete_id) {
+                let athlete_score = SCORES.may_load(
+                    deps.storage,
+                    (slot.athlete_id.as_str(), U64Key::from(contest.game_week)),
+                )?;
+                match athlete_score {
+                    Some(athlete_score) if athlete_score.status == ScoreStatus::Finalized => {
+                        points.insert(slot.athlete_id.clone(), athlete_score.points);
+                    }
+                    _ => return Err(ContractError::ScoreNotFinal {
+                        athlete_id: slot.athlete_id.clone(),
+                        game_week: contest.game_week,
+                    }),
+                }
+            }
+            score = score + decimal_mul(points[&slot.athlete_id], *game_rules.multipliers.get(slot.rarity));
         }
+        scored.push((score, entry));
     }
 
-    // Select random Athlete Token/Address from the mintable list
-    let index_list = hex_to_athlete(deps.branch().as_ref(), rand_seed).unwrap();
-    let athlete_id = mintable_list[index_list[0] as usize].to_string();
-    let token_id = generate_token_id(deps.branch().as_ref(), athlete_id.clone(), rarity.clone()).unwrap();
+    // Highest score first, ties go to the earliest entry
+    scored.sort_by(|(a_score, a), (b_score, b)| {
+        b_score.cmp(a_score).then(a.entry_id.cmp(&b.entry_id))
+    });
+
+    let mut paid = Uint128::zero();
+    for (i, (score, entry)) in scored.into_iter().enumerate() {
+        let prize = match (&contest.prize_pool, contest.payouts.get(i)) {
+            (Some(prize_pool), Some(payout)) => Some(Price {
+                denom: prize_pool.denom.clone(),
+                amount: prize_pool.amount * *payout,
+            }),
+            _ => None,
+        };
+        if let Some(prize) = &prize {
+            increase_claimable(deps.storage, &entry.owner, prize)?;
+            paid = paid.checked_add(prize.amount).map_err(StdError::from)?;
+        }
 
-    let mut usage_cap = 3;
-    if rarity.clone().eq("U"){
-        usage_cap = 5;
-    } else if rarity.clone().eq("R"){
-        usage_cap = 10;
-    } else if rarity.clone().eq("L"){
-        usage_cap = 999_999_999;
+        let rank = i as u64 + 1;
+        LEADERBOARDS.save(deps.storage, (U64Key::from(contest_id), U64Key::from(rank)), &Standing {
+            rank,
+            entry_id: entry.entry_id,
+            owner: entry.owner,
+            score,
+            prize,
+        })?;
     }
 
-    // Mint higher rarity token
-    let mint_msg = TokenMsg::Mint {
-        token_id: token_id,
-        owner: sender.clone().to_string(),
-        token_uri: None,
-        extension: TokenExtension {
-            athlete_id: athlete_id.clone(),
-            rarity: rarity.clone(),
-            is_locked: false,
-            unlock_date: None,
-            usage: usage_cap,
+    // Prizes of ranks nobody reached go back to the prize pool
+    if let Some(prize_pool) = &contest.prize_pool {
+        let unpaid = prize_pool.amount.checked_sub(paid).map_err(StdError::from)?;
+        if !unpaid.is_zero() {
+            increase_prize_pool(deps.storage, &Price {
+                denom: prize_pool.denom.clone(),
+                amount: unpaid,
+            })?;
         }
-    };
-    
-    response = response.add_message(WasmMsg::Execute {
-        contract_addr: athlete_contract.clone().to_string(),
-        msg: to_binary(&mint_msg).unwrap(),
-        funds: vec![],
-    });
+    }
 
-    update_token_count(deps.branch(), athlete_id.clone(), rarity.clone())?;
-    
-    Ok(response)
-}
+    contest.settled = true;
+    CONTESTS.save(deps.storage, U64Key::from(contest_id), &contest)?;
 
-pub fn update_last_round(
-    deps: DepsMut,
-    _env: Env,
-    new_round: &u64,
-) -> StdResult<u64> {
-    LAST_ROUND.save(deps.storage, &new_round)?;
-    Ok(*new_round)
+    Ok(Response::new()
+        .add_attribute("action", "settle_contest")
+        .add_attribute("contest_id", contest_id.to_string())
+        .add_attribute("entries", contest.entries.to_string())
+        .add_attribute("paid", paid.to_string()))
 }
 
-// updates the number of tokens of a given athlete id
-fn update_token_count (
+pub fn execute_submit_scores(
     deps: DepsMut,
-    athlete_id: String,
-    rarity: String,
+    env: Env,
+    info: MessageInfo,
+    scores: Vec<ScoreMsg>,
 ) -> Result<Response, ContractError> {
-    let mut athlete_info = query_athlete_info(deps.as_ref(), athlete_id.clone()).unwrap();
+    let contract_info = query_contract_info(deps.as_ref())?;
 
-    if rarity.eq("U"){
-        athlete_info.uncommon_count = athlete_info.uncommon_count + 1;
-    } else if rarity.eq("R"){
-        athlete_info.rare_count = athlete_info.rare_count + 1;
-    } else if rarity.eq("L"){
-        athlete_info.legendary_count = athlete_info.legendary_count + 1;
-    } else {
-        athlete_info.common_count = athlete_info.common_count + 1;
+    if !contract_info.scorers.contains(&info.sender) {
+        return Err(ContractError::Unauthorized{})
     }
 
-    athlete_list(deps.storage).update::<_, ContractError>(&athlete_id.clone().as_bytes(), |old| match old {
-        Some(_) => Ok(athlete_info),
-        None => Err(ContractError::DoesNotExist {}),
-    })?;
+    let dispute_ends = env.block.time.plus_seconds(contract_info.dispute_period);
+
+    for score in scores.iter() {
+        if athlete_list_read(deps.storage).may_load(score.athlete_id.as_bytes())?.is_none() {
+            return Err(ContractError::DoesNotExist {});
+        }
+
+        let key = (score.athlete_id.as_str(), U64Key::from(score.game_week));
+        if SCORES.has(deps.storage, key.clone()) {
+            return Err(ContractError::ScoreExists {
+                athlete_id: score.athlete_id.clone(),
+                game_week: score.game_week,
+            });
+        }
+
+        SCORES.save(deps.storage, key, &AthleteScore {
+            athlete_id: score.athlete_id.clone(),
+            game_week: score.game_week,
+            points: score.points,
+            scorer: info.sender.clone(),
+            dispute_ends,
+            status: ScoreStatus::Submitted,
+        })?;
+    }
 
     Ok(Response::new()
-        .add_attribute("action", "update_token_count")
-        .add_attribute("athlete_id", athlete_id.clone())
-        .add_attribute("rarity", rarity.clone()))
+        .add_attribute("action", "submit_scores")
+        .add_attribute("from", &info.sender)
+        .add_attribute("count", scores.len().to_string()))
 }
 
-// returns a string containing contract symbol + token rarity + token count
-fn generate_token_id (
-    deps: Deps,
+pub fn execute_correct_score(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
     athlete_id: String,
-    rarity: String,
-) -> StdResult<String> {
-
-    let athlete_info = query_athlete_info(deps, athlete_id).unwrap();
-    let mut token_id: String = athlete_info.symbol;
-    
-    let mut rarity_string: &str = "C";
-    let mut token_count = athlete_info.common_count + 1;
-    
-    if rarity.eq("U"){
-        rarity_string = "U"; 
-        token_count = athlete_info.uncommon_count + 1;
-    } else if rarity.eq("R"){
-        rarity_string = "R";
-        token_count = athlete_info.rare_count + 1;
-    } else if rarity.eq("L"){
-        rarity_string = "L";
-        token_count = athlete_info.legendary_count + 1;
-    } 
+    game_week: u64,
+    points: Decimal,
+) -> Result<Response, ContractError> {
+    let admin_addr = query_contract_info(deps.as_ref())?.admin_addr;
 
-    token_id.push_str(rarity_string);
-    token_id.push_str(&token_count.to_string());
+    if info.sender != admin_addr {
+        return Err(ContractError::Unauthorized{})
+    }
 
-    Ok(token_id)
-}
+    let key = (athlete_id.as_str(), U64Key::from(game_week));
+    let mut score = SCORES.load(deps.storage, key.clone())?;
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::ContractInfo {} => to_binary(&query_contract_info(deps)?),
-        QueryMsg::PackPrice {} => to_binary(&query_pack_price(deps)?),
-        QueryMsg::TotalDeposit {} => to_binary(&query_total_deposit(deps)?),
-        QueryMsg::AthleteInfo {
-            athlete_id
-        } => to_binary(&query_athlete_info(deps, athlete_id)?),
-        QueryMsg::AthleteCount {} => to_binary(&query_athlete_count(deps)?),
-        QueryMsg::IsTokenMintable {
-            athlete_id,
-            rarity,
-        } => to_binary(&query_token_mintable(deps, athlete_id, rarity)?),
-        QueryMsg::CanUnlockToken {
-            token_id
-        } => to_binary(&query_unlock_token(deps, env, token_id)?),
-        QueryMsg::CanUseToken {
-            token_id
-        } => to_binary(&query_use_token(deps, token_id)?),
+    if score.status == ScoreStatus::Finalized {
+        return Err(ContractError::ScoreFinalized { athlete_id, game_week });
+    }
+    if env.block.time >= score.dispute_ends {
+        return Err(ContractError::DisputePeriodEnded { athlete_id, game_week });
     }
-}
 
-fn query_contract_info(
-    deps: Deps,
-) -> StdResult<ContractInfoResponse> {
-    CONTRACT_INFO.load(deps.storage)
+    score.points = points;
+    score.status = ScoreStatus::Corrected;
+    SCORES.save(deps.storage, key, &score)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "correct_score")
+        .add_attribute("athlete_id", athlete_id)
+        .add_attribute("game_week", game_week.to_string())
+        .add_attribute("points", points.to_string()))
 }
 
-fn query_total_deposit(
-    deps: Deps,
-) -> StdResult<Uint128> {
-    Ok(Uint128::from(total_deposit(deps.storage)?))
-}
+pub fn execute_finalize_scores(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    athlete_ids: Vec<String>,
+    game_week: u64,
+) -> Result<Response, ContractError> {
+    for athlete_id in athlete_ids.iter() {
+        let key = (athlete_id.as_str(), U64Key::from(game_week));
+        let mut score = SCORES.load(deps.storage, key.clone())?;
 
-fn query_pack_price(deps: Deps) -> StdResult<u64> {
-    Ok(CONTRACT_INFO.load(deps.storage)?.pack_price)
-}
+        if score.status == ScoreStatus::Finalized {
+            return Err(ContractError::ScoreFinalized { athlete_id: athlete_id.clone(), game_week });
+        }
+        if env.block.time < score.dispute_ends {
+            return Err(ContractError::DisputePeriodActive { athlete_id: athlete_id.clone(), game_week });
+        }
 
-fn query_athlete_info(
-    deps: Deps,
-    athlete_id: String
-) -> StdResult<AthleteInfo> {
-    athlete_list_read(deps.storage).load(athlete_id.as_bytes())
-}
+        score.status = ScoreStatus::Finalized;
+        SCORES.save(deps.storage, key, &score)?;
+    }
 
-fn query_athlete_count(
-    deps: Deps,
-) -> StdResult<u64> {
-    Ok(athlete_count(deps.storage)?)
+    Ok(Response::new()
+        .add_attribute("action", "finalize_scores")
+        .add_attribute("game_week", game_week.to_string())
+        .add_attribute("count", athlete_ids.len().to_string()))
 }
 
-fn query_token_mintable(
-    deps: Deps,
-    athlete_id: String,
-    rarity: String
-) -> StdResult<bool> {
-    let contract_info = query_contract_info(deps).unwrap();
-    let athlete_info = query_athlete_info(deps, athlete_id).unwrap();
-    let mut is_mintable = true;
+pub fn execute_update_scorers(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    scorers: Vec<String>,
+) -> Result<Response, ContractError> {
+    let contract_info = query_contract_info(deps.as_ref())?;
 
-    if rarity.eq("U"){
-        if athlete_info.uncommon_count == contract_info.uncommon_cap {
-            is_mintable = false
-        }
-    } else if rarity.eq("R"){
-        if athlete_info.rare_count == contract_info.rare_cap {
-            is_mintable = false
-        }
-    } else if rarity.eq("L"){
-        if athlete_info.legendary_count == contract_info.legendary_cap {
-            is_mintable = false
-        }
-    } else  {
-        if athlete_info.common_count == contract_info.common_cap {
-            is_mintable = false
-        }
+    if info.sender != contract_info.admin_addr {
+        return Err(ContractError::Unauthorized{})
     }
 
-    Ok(is_mintable)
+    let scorers = scorers
+        .iter()
+        .map(|scorer| deps.api.addr_validate(scorer))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let info = ContractInfoResponse {
+        scorers,
+        ..contract_info
+    };
+
+    CONTRACT_INFO.save(deps.storage, &info)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_scorers"))
 }
 
-fn query_token_info(
-    deps: Deps,
-    token_id: String
-) -> StdResult<NftInfoResponse> {
-    let token_address = query_contract_info(deps).unwrap().athlete_addr;
+pub fn execute_update_dispute_period(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    dispute_period: u64,
+) -> Result<Response, ContractError> {
+    let contract_info = query_contract_info(deps.as_ref())?;
 
-    let msg = TokenMsg::NftInfo { token_id: token_id };
-    let wasm = WasmQuery::Smart {
-        contract_addr: token_address.to_string(),
-        msg: to_binary(&msg)?,
+    if info.sender != contract_info.admin_addr {
+        return Err(ContractError::Unauthorized{})
+    }
+
+    let info = ContractInfoResponse {
+        dispute_period,
+        ..contract_info
     };
 
-    let token = deps.querier.query::<NftInfoResponse>(&wasm.into())?;
+    CONTRACT_INFO.save(deps.storage, &info)?;
 
-    let nft_info = NftInfoResponse {
-        token_uri: token.token_uri,
-        extension: TokenExtension {
-            athlete_id: token.extension.athlete_id,
-            rarity: token.extension.rarity,
-            is_locked: token.extension.is_locked,
-            unlock_date: token.extension.unlock_date,
-            usage: token.extension.usage,
+    Ok(Response::new()
+        .add_attribute("action", "update_dispute_period")
+        .add_attribute("dispute_period", dispute_period.to_string()))
+}
+
+pub fn execute_add_athlete(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    athletes: Vec<AthleteMsg>,
+) -> Result<Response, ContractError> {
+    let admin_addr = query_contract_info(deps.as_ref())?.admin_addr;
+
+    if info.sender != admin_addr {
+        return Err(ContractError::Unauthorized{})
+    }
+
+    let mut response = Response::new()
+        .add_attribute("action", "add_tokens");
+
+    for athlete in athletes {
+        let symbol = athlete.symbol;
+        let athlete_id = query_athlete_count(deps.as_ref())?;
+
+        if symbol.is_empty() || symbol.contains(TOKEN_ID_SEPARATOR) {
+            return Err(ContractError::InvalidSymbol { symbol });
         }
-    };
 
-    Ok(nft_info)
+        // Symbols are used for generating token IDs so they have to be unique
+        ATHLETE_SYMBOLS.update::<_, ContractError>(deps.storage, &symbol, |old| match old {
+            Some(_) => Err(ContractError::SymbolClaimed { symbol: symbol.clone() }),
+            None => Ok(athlete_id),
+        })?;
+
+        let athlete_info = AthleteInfo {
+            symbol: symbol.clone(),
+            minted: PerRarity::default(),
+            metadata: athlete.metadata,
+            is_retired: false,
+            caps: athlete.caps,
+            burned: PerRarity::default(),
+        };
+
+        athlete_list(deps.storage).update::<_, ContractError>(&athlete_id.to_string().as_bytes(), |old| match old {
+            Some(_) => Err(ContractError::Claimed {}),
+            None => Ok(athlete_info.clone()),
+        })?;
+        
+        increment_athlete_count(deps.storage)?;
+
+        response = response.add_attribute("symbol", symbol.clone());
+    }
+    
+    Ok(response)
 }
 
-fn query_unlock_token(
-    deps: Deps,
-    env: Env,
-    token_id: String
-) -> StdResult<bool> {
-    let token = query_token_info(deps, token_id.clone()).unwrap();
-    let mut can_unlock = false;
-    let curr_date = env.block.time;
-    let unlock_date = token.extension.unlock_date.unwrap_or_default();
-    let zero = Timestamp::from_nanos(0);
+pub fn execute_update_athlete(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    athlete_id: String,
+    metadata: AthleteMetadata,
+) -> Result<Response, ContractError> {
+    let admin_addr = query_contract_info(deps.as_ref())?.admin_addr;
 
-    if curr_date.minus_seconds(unlock_date.seconds()).nanos() >= zero.nanos() {
-        can_unlock = true;
+    if info.sender != admin_addr {
+        return Err(ContractError::Unauthorized{})
     }
 
-    Ok(can_unlock)
+    athlete_list(deps.storage).update::<_, ContractError>(athlete_id.as_bytes(), |old| match old {
+        Some(athlete_info) => Ok(AthleteInfo { metadata, ..athlete_info }),
+        None => Err(ContractError::DoesNotExist {}),
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_athlete")
+        .add_attribute("athlete_id", athlete_id))
 }
 
-fn query_use_token(
-    deps: Deps,
-    token_id: String
-) -> StdResult<bool> {
-    let token = query_token_info(deps, token_id.clone()).unwrap();
-    let mut can_use = false;
+pub fn execute_update_athlete_caps(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    athlete_id: String,
+    caps: AthleteCaps,
+) -> Result<Response, ContractError> {
+    let admin_addr = query_contract_info(deps.as_ref())?.admin_addr;
 
-    if token.extension.usage > 0 {
-        can_use = true;
+    if info.sender != admin_addr {
+        return Err(ContractError::Unauthorized{})
     }
 
-    Ok(can_use)
+    athlete_list(deps.storage).update::<_, ContractError>(athlete_id.as_bytes(), |old| match old {
+        Some(athlete_info) => Ok(AthleteInfo { caps, ..athlete_info }),
+        None => Err(ContractError::DoesNotExist {}),
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_athlete_caps")
+        .add_attribute("athlete_id", athlete_id))
 }
 
-fn _query_terrand(
+pub fn execute_set_athlete_retired(
     deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    athlete_id: String,
+    is_retired: bool,
+) -> Result<Response, ContractError> {
+    let admin_addr = query_contract_info(deps.as_ref())?.admin_addr;
+
+    if info.sender != admin_addr {
+        return Err(ContractError::Unauthorized{})
+    }
+
+    athlete_list(deps.storage).update::<_, ContractError>(athlete_id.as_bytes(), |old| match old {
+        Some(athlete_info) => Ok(AthleteInfo { is_retired, ..athlete_info }),
+        None => Err(ContractError::DoesNotExist {}),
+    })?;
+
+    let action = if is_retired { "retire_athlete" } else { "reactivate_athlete" };
+
+    Ok(Response::new()
+        .add_attribute("action", action)
+        .add_attribute("athlete_id", athlete_id))
+}
+
+pub fn execute_token_turnover(
+    mut deps: DepsMut,
     env: Env,
-    count: u64
-) -> StdResult<String> {
-    // Load terrand_addr from the state
-    let terrand_addr = query_contract_info(deps.as_ref()).unwrap().terrand_addr;
-    //let last_round = query_last_round(deps.as_ref()).unwrap();
-    // String length to be returned by terrand should have 3 characters per athlete ID
-    let string_len = count * 3;
+    info: MessageInfo,
+    new_contract: String,
+) -> Result<Response, ContractError> {
+    let new_address = deps.api.addr_validate(&new_contract)?;
+    let contract_info = query_contract_info(deps.branch().as_ref()).unwrap();
 
-    let msg = TerrandMsg::LatestDrand {};
-    let wasm = encode_msg_query(
-        to_binary(&msg).unwrap(),
-        terrand_addr
+    if info.sender != contract_info.admin_addr {
+        return Err(ContractError::Unauthorized{})
+    }
+
+    let mut response = Response::new()
+        .add_attribute("action", "token_turnover")
+        .add_attribute("from", &env.contract.address)
+        .add_attribute("to", &new_address.to_string());
+
+    let update_msg = TokenMsg::UpdateMinter {
+        minter: new_address.clone().to_string(),
+    };
+
+    let token_res = encode_msg_execute(
+        to_binary(&update_msg).unwrap(),
+        contract_info.athlete_addr.clone(),
+        vec![]
     )?;
-    
-    let terrand_res: LatestRandomResponse = deps.querier.query(&wasm.into())?;
-    let randomness_hash = hex::encode(terrand_res.randomness.as_slice());
 
-    // if terrand_res.round <= last_round {
-    //     return Err(StdError::generic_err("The current round has already been used. Please wait for the next round."))
-    // }
+    // TODO: handle error from token_res
+    response = response.add_message(token_res);
 
-    update_last_round(deps, env, &terrand_res.round)?;
+    Ok(response)
+}
 
-    let n = randomness_hash
-        .char_indices()
-        .rev()
-        .nth(string_len as usize - 1)
-        .map(|(i, _)| i)
-        .unwrap();
-    let random_string = &randomness_hash[n..];
-   
-    Ok(random_string.to_string())
+pub fn execute_update_cw721(
+    mut deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    new_contract: String,
+) -> Result<Response, ContractError> {
+    let contract_info = query_contract_info(deps.as_ref()).unwrap();
+    let new_address = deps.api.addr_validate(&new_contract)?;
+    let old_address = contract_info.athlete_addr;
+
+    if info.sender != contract_info.admin_addr.clone() {
+        return Err(ContractError::Unauthorized{})
+    }
+
+    let info = ContractInfoResponse {
+        athlete_addr: new_address.clone(),
+        ..contract_info
+    };
+
+    CONTRACT_INFO.save(deps.branch().storage, &info)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_cw721")
+        .add_attribute("from", old_address.clone())
+        .add_attribute("to", new_address.clone()))
 }
 
-fn hex_to_athlete(
-    deps: Deps,
-    rand_seed: String
-) -> StdResult<Vec<u64>> {
+pub fn execute_update_marketplace(
+    mut deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    new_contract: String,
+) -> Result<Response, ContractError> {
+    let contract_info = query_contract_info(deps.as_ref()).unwrap();
+    let new_address = deps.api.addr_validate(&new_contract)?;
+    let old_address = contract_info.marketplace_addr;
+
+    if info.sender != contract_info.admin_addr.clone() {
+        return Err(ContractError::Unauthorized{})
+    }
+
+    let info = ContractInfoResponse {
+        marketplace_addr: new_address.clone(),
+        ..contract_info
+    };
 
-    // Splits rand_seed into a vector of 3 character strings 
-    let hex_list = rand_seed.chars()
-        .collect::<Vec<char>>()
-        .chunks(3)
-        .map(|c| c.iter().collect::<String>())
-        .collect::<Vec<String>>();
+    CONTRACT_INFO.save(deps.branch().storage, &info)?;
 
-    // Load athlete_count from the state
-    let athlete_count = query_athlete_count(deps).unwrap();
+    Ok(Response::new()
+        .add_attribute("action", "update_marketplace")
+        .add_attribute("from", old_address.clone())
+        .add_attribute("to", new_address.clone()))
+}
 
-    let mut athlete_list: Vec<u64> = Vec::new();
-    for hex in hex_list.iter(){
-        // Convert hexadecimal to decimal
-        let deci = u64::from_str_radix(hex, 16).unwrap();
-        // Get the athlete IDs by using modulo
-        let athlete_id = deci % athlete_count;
+pub fn execute_update_deposit_share(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    deposit_share: Decimal,
+) -> Result<Response, ContractError> {
+    let contract_info = query_contract_info(deps.as_ref())?;
 
-        athlete_list.push(athlete_id);
+    if info.sender != contract_info.admin_addr {
+        return Err(ContractError::Unauthorized{})
     }
 
-    Ok(athlete_list)
-}
\ No newline at end of file
+    validate_deposit_share(deposit_share)?;
+
+    let info = ContractInfoResponse {
+        deposit_share,
+        ..contract_info
+    };
+
+    CONTRACT_INFO.save(deps.storage, &info)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_deposit_share")
+        .add_attribute("deposit_share", deposit_share.to_string()))
+}
+
+pub fn execute_update_reveal_timeout(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    reveal_timeout: u64,
+) -> Result<Response, ContractError> {
+    let contract_info = query_contract_info(deps.as_ref())?;
+
+    if info.sender != contract_info.admin_addr {
+        return Err(ContractError::Unauthorized{})
+    }
+
+    validate_reveal_timeout(reveal_timeout)?;
+
+    let info = ContractInfoResponse {
+        reveal_timeout,
+        ..contract_info
+    };
+
+    CONTRACT_INFO.save(deps.storage, &info)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_reveal_timeout")
+        .add_attribute("reveal_timeout", reveal_timeout.to_string()))
+}
+
+pub fn execute_update_accepted_tokens(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    tokens: Vec<String>,
+) -> Result<Response, ContractError> {
+    let contract_info = query_contract_info(deps.as_ref())?;
+
+    if info.sender != contract_info.admin_addr {
+        return Err(ContractError::Unauthorized{})
+    }
+
+    let accepted_tokens = tokens
+        .iter()
+        .map(|token| deps.api.addr_validate(token))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let info = ContractInfoResponse {
+        accepted_tokens,
+        ..contract_info
+    };
+
+    CONTRACT_INFO.save(deps.storage, &info)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_accepted_tokens"))
+}
+
+pub fn execute_add_pack_type(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    pack_type: PackType,
+) -> Result<Response, ContractError> {
+    let admin_addr = query_contract_info(deps.as_ref())?.admin_addr;
+
+    if info.sender != admin_addr {
+        return Err(ContractError::Unauthorized{})
+    }
+
+    validate_pack_type(deps.as_ref(), &pack_type)?;
+
+    if PACK_TYPES.has(deps.storage, &pack_type.name) {
+        return Err(ContractError::PackTypeExists { pack_type: pack_type.name });
+    }
+
+    PACK_TYPES.save(deps.storage, &pack_type.name, &pack_type)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "add_pack_type")
+        .add_attribute("pack_type", pack_type.name))
+}
+
+pub fn execute_update_pack_type(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    pack_type: PackType,
+) -> Result<Response, ContractError> {
+    let admin_addr = query_contract_info(deps.as_ref())?.admin_addr;
+
+    if info.sender != admin_addr {
+        return Err(ContractError::Unauthorized{})
+    }
+
+    validate_pack_type(deps.as_ref(), &pack_type)?;
+
+    // Fails if the pack type does not exist
+    PACK_TYPES.load(deps.storage, &pack_type.name)?;
+    PACK_TYPES.save(deps.storage, &pack_type.name, &pack_type)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_pack_type")
+        .add_attribute("pack_type", pack_type.name))
+}
+
+pub fn execute_update_sale_phases(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    mut phases: Vec<SalePhase>,
+) -> Result<Response, ContractError> {
+    let admin_addr = query_contract_info(deps.as_ref())?.admin_addr;
+
+    if info.sender != admin_addr {
+        return Err(ContractError::Unauthorized{})
+    }
+
+    phases.sort_by_key(|phase| phase.start_time);
+    validate_sale_phases(&phases)?;
+
+    SALE_PHASES.save(deps.storage, &phases)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_sale_phases"))
+}
+
+pub fn execute_update_game_rules(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    game_rules: GameRules,
+) -> Result<Response, ContractError> {
+    let admin_addr = query_contract_info(deps.as_ref())?.admin_addr;
+
+    if info.sender != admin_addr {
+        return Err(ContractError::Unauthorized{})
+    }
+
+    validate_game_rules(&game_rules)?;
+    GAME_RULES.save(deps.storage, &game_rules)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_game_rules"))
+}
+
+pub fn execute_receive_nft(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw721ReceiveMsg,
+) -> Result<Response, ContractError> {
+    // info.sender is the NFT contract the token was sent from
+    if info.sender != query_contract_info(deps.as_ref())?.athlete_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let owner = deps.api.addr_validate(&wrapper.sender)?;
+
+    match from_binary(&wrapper.msg)? {
+        NftReceiveMsg::LockToken {
+            duration
+        } => execute_lock_token(deps, env, owner, wrapper.token_id, duration),
+    }
+}
+
+pub fn execute_lock_token(
+    deps: DepsMut,
+    env: Env,
+    owner: Addr,
+    token_id: String,
+    duration: String
+) -> Result<Response, ContractError> {
+    let token_address = query_contract_info(deps.as_ref())?.athlete_addr;
+    let token = query_token_info(deps.as_ref(), token_id.clone())?;
+
+    if token.extension.usage == 0 {
+        return Err(ContractError::UsageCapped {});
+    }
+
+    let lock_seconds = match query_game_rules(deps.as_ref())?.lock_seconds(&duration) {
+        Some(seconds) => seconds,
+        None => return Err(ContractError::UnknownDuration { duration }),
+    };
+
+    // The contract holds the token until the owner unlocks it
+    let unlock_date = env.block.time.plus_seconds(lock_seconds);
+    locked_tokens().save(deps.storage, &token_id, &LockedToken {
+        token_id: token_id.clone(),
+        owner: owner.clone(),
+        duration: duration.clone(),
+        unlock_date,
+    })?;
+
+    let update_msg = TokenMsg::UpdateToken {
+        token_id: token_id.clone(),
+        token_uri: token.token_uri,
+        extension: TokenExtension {
+            usage: token.extension.usage - 1,
+            ..token.extension
+        }
+    };
+    
+    Ok(Response::new()
+        .add_message(WasmMsg::Execute {
+            contract_addr: token_address.to_string(),
+            msg: to_binary(&update_msg)?,
+            funds: vec![],
+        })
+        .add_attribute("action", "lock_token")
+        .add_attribute("owner", owner)
+        .add_attribute("token_id", token_id)
+        .add_attribute("duration", duration)
+        .add_attribute("unlock_date", unlock_date.to_string()))
+}
+
+pub fn execute_unlock_token(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let token_address = query_contract_info(deps.as_ref())?.athlete_addr;
+    let locked_token = locked_tokens().load(deps.storage, &token_id)?;
+
+    if info.sender != locked_token.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if env.block.time < locked_token.unlock_date {
+        return Err(ContractError::Locked {});
+    }
+
+    locked_tokens().remove(deps.storage, &token_id)?;
+
+    let transfer_msg = TokenMsg::TransferNft {
+        recipient: locked_token.owner.to_string(),
+        token_id: token_id.clone(),
+    };
+    
+    Ok(Response::new()
+        .add_message(WasmMsg::Execute {
+            contract_addr: token_address.to_string(),
+            msg: to_binary(&transfer_msg)?,
+            funds: vec![],
+        })
+        .add_attribute("action", "unlock_token")
+        .add_attribute("owner", locked_token.owner)
+        .add_attribute("token_id", token_id))
+}
+
+pub fn execute_upgrade_token(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipe: String,
+    tokens: Vec<String>,
+) -> Result<Response, ContractError> {
+    let athlete_contract = query_contract_info(deps.as_ref())?.athlete_addr;
+    let recipe = RECIPES.load(deps.storage, &recipe)?;
+    let game_rules = query_game_rules(deps.as_ref())?;
+    let sender = info.sender;
+
+    // Every input is checked before anything is burned
+    let inputs = validate_recipe_inputs(deps.as_ref(), &env, &sender, &recipe, &tokens)?;
+    let fee = check_upgrade_fee(&recipe.name, &info.funds, recipe.fee.as_ref().or(game_rules.upgrade_fee.as_ref()))?;
+
+    let mut response = Response::new()
+        .add_attribute("action", "upgrade_token")
+        .add_attribute("from", sender.clone())
+        .add_attribute("recipe", recipe.name.clone());
+
+    // Burn fodder tokens
+    for (token, input) in tokens.iter().zip(inputs.iter()) {
+        let burn_msg = TokenMsg::Burn {
+            token_id: token.clone()
+        };
+
+        response = response.add_message(WasmMsg::Execute {
+            contract_addr: athlete_contract.to_string(),
+            msg: to_binary(&burn_msg)?,
+            funds: vec![],
+        });
+
+        update_burn_count(deps.branch(), input.athlete_id.clone(), input.rarity)?;
+    }
+
+    let rarity = recipe.output_rarity;
+    if !recipe.same_athlete {
+        if query_mintable_athletes(deps.as_ref(), rarity, None, &[])?.is_empty() {
+            return Err(ContractError::Capped {});
+        }
+
+        // Randomness already on chain could be simulated before sending the upgrade, so the
+        // output athlete is drawn from a future round like the athletes of a pack
+        let upgrade_id = increment_upgrade_count(deps.storage)?;
+        let round = next_drand_round(env.block.time);
+        let upgrade = PendingUpgrade {
+            upgrade_id,
+            owner: sender,
+            recipe: recipe.name,
+            rarity,
+            inputs: tokens
+                .into_iter()
+                .zip(inputs.into_iter())
+                .map(|(token_id, input)| UpgradeInput {
+                    token_id,
+                    athlete_id: input.athlete_id,
+                    rarity: input.rarity,
+                    usage: input.usage,
+                })
+                .collect(),
+            fee,
+            round,
+            status: UpgradeStatus::Pending,
+            randomness: None,
+            token_id: None,
+        };
+        upgrades().save(deps.storage, U64Key::from(upgrade_id), &upgrade)?;
+
+        return Ok(response
+            .add_attribute("upgrade_id", upgrade_id.to_string())
+            .add_attribute("round", round.to_string()));
+    }
+
+    let athlete_id = inputs[0].athlete_id.clone();
+    if !query_token_mintable(deps.as_ref(), athlete_id.clone(), rarity)? {
+        return Err(ContractError::Capped {});
+    }
+
+    // Mint higher rarity token
+    let token = draw_token(deps.as_ref(), athlete_id, rarity, &[])?;
+    let token_id = token.token_id.clone();
+    let mint_msg = mint_token(deps.branch(), &sender, token, &game_rules)?;
+    if let Some(fee) = &fee {
+        distribute_upgrade_fee(deps.storage, fee)?;
+    }
+
+    Ok(response
+        .add_message(mint_msg)
+        .add_attribute("token_id", token_id))
+}
+
+pub fn execute_reveal_upgrade(
+    mut deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    upgrade_id: u64,
+) -> Result<Response, ContractError> {
+    let game_rules = query_game_rules(deps.as_ref())?;
+    let mut upgrade = upgrades().load(deps.storage, U64Key::from(upgrade_id))?;
+
+    if upgrade.status != UpgradeStatus::Pending {
+        return Err(ContractError::UpgradeNotPending {});
+    }
+
+    let randomness = query_terrand_round(deps.as_ref(), upgrade.round)?
+        .ok_or(ContractError::RoundNotAvailable { round: upgrade.round })?;
+
+    let token = draw_upgrade(deps.as_ref(), &upgrade, &randomness)?;
+    let token_id = token.token_id.clone();
+    let mint_msg = mint_token(deps.branch(), &upgrade.owner, token, &game_rules)?;
+
+    // The fee leaves escrow once the upgraded token is minted
+    if let Some(fee) = &upgrade.fee {
+        distribute_upgrade_fee(deps.storage, fee)?;
+    }
+
+    upgrade.status = UpgradeStatus::Revealed;
+    upgrade.randomness = Some(randomness);
+    upgrade.token_id = Some(token_id.clone());
+    upgrades().save(deps.storage, U64Key::from(upgrade_id), &upgrade)?;
+
+    Ok(Response::new()
+        .add_message(mint_msg)
+        .add_attribute("action", "reveal_upgrade")
+        .add_attribute("owner", &upgrade.owner)
+        .add_attribute("upgrade_id", upgrade_id.to_string())
+        .add_attribute("round", upgrade.round.to_string())
+        .add_attribute("token_id", token_id))
+}
+
+pub fn execute_refund_upgrade(
+    mut deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    upgrade_id: u64,
+) -> Result<Response, ContractError> {
+    let athlete_contract = query_contract_info(deps.as_ref())?.athlete_addr;
+    let mut upgrade = upgrades().load(deps.storage, U64Key::from(upgrade_id))?;
+
+    if info.sender != upgrade.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if upgrade.status != UpgradeStatus::Pending {
+        return Err(ContractError::UpgradeNotPending {});
+    }
+
+    // Only upgrades whose output can never be minted are refunded, the others are revealed
+    let randomness = query_terrand_round(deps.as_ref(), upgrade.round)?
+        .ok_or(ContractError::RoundNotAvailable { round: upgrade.round })?;
+    if !is_draw_blocked(draw_upgrade(deps.as_ref(), &upgrade, &randomness))? {
+        return Err(ContractError::UpgradeRevealable {});
+    }
+
+    let mut response = Response::new()
+        .add_attribute("action", "refund_upgrade")
+        .add_attribute("owner", &upgrade.owner)
+        .add_attribute("upgrade_id", upgrade_id.to_string());
+
+    // Inputs are minted back with their IDs, they still count as minted against the caps
+    for input in upgrade.inputs.iter() {
+        let mint_msg = TokenMsg::Mint {
+            token_id: input.token_id.clone(),
+            owner: upgrade.owner.to_string(),
+            token_uri: None,
+            extension: TokenExtension {
+                athlete_id: input.athlete_id.clone(),
+                rarity: input.rarity,
+                is_locked: false,
+                unlock_date: None,
+                usage: input.usage,
+            }
+        };
+
+        response = response.add_message(WasmMsg::Execute {
+            contract_addr: athlete_contract.to_string(),
+            msg: to_binary(&mint_msg)?,
+            funds: vec![],
+        });
+
+        restore_burn_count(deps.branch(), input.athlete_id.clone(), input.rarity)?;
+    }
+
+    if let Some(fee) = &upgrade.fee {
+        response = response.add_message(BankMsg::Send {
+            to_address: upgrade.owner.to_string(),
+            amount: vec![fee.clone()],
+        });
+    }
+
+    upgrade.status = UpgradeStatus::Refunded;
+    upgrade.randomness = Some(randomness);
+    upgrades().save(deps.storage, U64Key::from(upgrade_id), &upgrade)?;
+
+    Ok(response)
+}
+
+// token drawn for a pack or an upgrade, only saved once every token of the draw is known
+#[derive(Debug)]
+struct DrawnToken {
+    athlete_id: String,
+    rarity: Rarity,
+    serial: u64,
+    token_id: String,
+}
+
+// draws the tokens of a pack without saving anything, so that a refund can check whether
+// the pack can still be opened
+fn draw_pack(
+    deps: Deps,
+    pack: &PackInfo,
+    randomness: &Binary,
+) -> Result<Vec<DrawnToken>, ContractError> {
+    // Packs sharing the same round get different draws by hashing in the pack ID
+    let seed = Sha256::digest(&[randomness.as_slice(), &pack.pack_id.to_be_bytes()].concat());
+    // Each slot uses one number for its rarity and one for its athlete
+    let random_numbers = randomness_to_numbers(
+        &Binary::from(seed.as_slice()),
+        pack.pack_len * 2,
+    );
+
+    let mut drawn = vec![];
+    for slot in random_numbers.chunks(2) {
+        let sampled_rarity = sample_rarity(&pack.odds, slot[0]);
+        // Mintable athletes are checked per slot since earlier slots may have reached a cap
+        let (rarity, mintable_token_list) = query_fallback_mintable(deps, sampled_rarity, pack, &drawn)?
+            .ok_or(ContractError::Capped {})?;
+        let index = slot[1] % mintable_token_list.len() as u64;
+        let athlete_id = mintable_token_list[index as usize].to_string();
+        let token = draw_token(deps, athlete_id, rarity, &drawn)?;
+        drawn.push(token);
+    }
+
+    Ok(drawn)
+}
+
+// draws the output token of a random upgrade without saving anything, so that a refund can
+// check whether the upgrade can still be revealed
+fn draw_upgrade(
+    deps: Deps,
+    upgrade: &PendingUpgrade,
+    randomness: &Binary,
+) -> Result<DrawnToken, ContractError> {
+    // Upgrades sharing a round with packs or other upgrades get different draws
+    let seed = Sha256::digest(&[randomness.as_slice(), b"upgrade", &upgrade.upgrade_id.to_be_bytes()].concat());
+    let random_numbers = randomness_to_numbers(&Binary::from(seed.as_slice()), 1);
+
+    // Mintable athletes are read at reveal time since caps may have been reached meanwhile
+    let mintable_list = query_mintable_athletes(deps, upgrade.rarity, None, &[])?;
+    if mintable_list.is_empty() {
+        return Err(ContractError::Capped {});
+    }
+    let index = random_numbers[0] % mintable_list.len() as u64;
+    let athlete_id = mintable_list[index as usize].to_string();
+
+    draw_token(deps, athlete_id, upgrade.rarity, &[])
+}
+
+// returns true if a draw failed because its tokens can no longer be minted
+fn is_draw_blocked<T>(draw: Result<T, ContractError>) -> Result<bool, ContractError> {
+    match draw {
+        Ok(_) => Ok(false),
+        Err(ContractError::Capped {}) | Err(ContractError::TokenIdTaken { .. }) => Ok(true),
+        Err(err) => Err(err),
+    }
+}
+
+// picks the ID of a new token of the athlete, after the serials of the tokens already drawn
+fn draw_token(
+    deps: Deps,
+    athlete_id: String,
+    rarity: Rarity,
+    drawn: &[DrawnToken],
+) -> Result<DrawnToken, ContractError> {
+    let last_serial = match drawn.iter().rev().find(|t| t.athlete_id == athlete_id && t.rarity == rarity) {
+        Some(token) => token.serial,
+        None => token_serial(deps.storage, &athlete_id, rarity)?,
+    };
+    let (serial, token_id) = next_token_id(deps, &athlete_id, rarity, last_serial)?;
+
+    Ok(DrawnToken {
+        athlete_id,
+        rarity,
+        serial,
+        token_id,
+    })
+}
+
+// saves the serial of a drawn token, counts it as minted and returns the message minting
+// it to the owner
+fn mint_token(
+    deps: DepsMut,
+    owner: &Addr,
+    token: DrawnToken,
+    game_rules: &GameRules,
+) -> Result<WasmMsg, ContractError> {
+    let athlete_contract = query_contract_info(deps.as_ref())?.athlete_addr;
+    save_token_serial(deps.storage, &token.athlete_id, token.rarity, token.serial)?;
+
+    let mint_msg = TokenMsg::Mint {
+        token_id: token.token_id,
+        owner: owner.to_string(),
+        token_uri: None,
+        extension: TokenExtension {
+            athlete_id: token.athlete_id.clone(),
+            rarity: token.rarity,
+            is_locked: false,
+            unlock_date: None,
+            usage: *game_rules.usage.get(token.rarity),
+        }
+    };
+
+    update_token_count(deps, token.athlete_id, token.rarity)?;
+
+    Ok(WasmMsg::Execute {
+        contract_addr: athlete_contract.to_string(),
+        msg: to_binary(&mint_msg)?,
+        funds: vec![],
+    })
+}
+
+pub fn execute_add_recipe(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    recipe: Recipe,
+) -> Result<Response, ContractError> {
+    let admin_addr = query_contract_info(deps.as_ref())?.admin_addr;
+
+    if info.sender != admin_addr {
+        return Err(ContractError::Unauthorized{})
+    }
+
+    validate_recipe(&recipe)?;
+
+    if RECIPES.has(deps.storage, &recipe.name) {
+        return Err(ContractError::RecipeExists { recipe: recipe.name });
+    }
+
+    RECIPES.save(deps.storage, &recipe.name, &recipe)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "add_recipe")
+        .add_attribute("recipe", recipe.name))
+}
+
+pub fn execute_update_recipe(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    recipe: Recipe,
+) -> Result<Response, ContractError> {
+    let admin_addr = query_contract_info(deps.as_ref())?.admin_addr;
+
+    if info.sender != admin_addr {
+        return Err(ContractError::Unauthorized{})
+    }
+
+    validate_recipe(&recipe)?;
+
+    // Fails if the recipe does not exist
+    RECIPES.load(deps.storage, &recipe.name)?;
+    RECIPES.save(deps.storage, &recipe.name, &recipe)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_recipe")
+        .add_attribute("recipe", recipe.name))
+}
+
+pub fn execute_remove_recipe(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    name: String,
+) -> Result<Response, ContractError> {
+    let admin_addr = query_contract_info(deps.as_ref())?.admin_addr;
+
+    if info.sender != admin_addr {
+        return Err(ContractError::Unauthorized{})
+    }
+
+    // Fails if the recipe does not exist
+    RECIPES.load(deps.storage, &name)?;
+    RECIPES.remove(deps.storage, &name);
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_recipe")
+        .add_attribute("recipe", name))
+}
+
+// updates the number of tokens of a given athlete id
+fn update_token_count (
+    deps: DepsMut,
+    athlete_id: String,
+    rarity: Rarity,
+) -> Result<Response, ContractError> {
+    let mut athlete_info = query_athlete_info(deps.as_ref(), athlete_id.clone())?;
+    *athlete_info.minted.get_mut(rarity) += 1;
+
+    athlete_list(deps.storage).update::<_, ContractError>(&athlete_id.clone().as_bytes(), |old| match old {
+        Some(_) => Ok(athlete_info),
+        None => Err(ContractError::DoesNotExist {}),
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_token_count")
+        .add_attribute("athlete_id", athlete_id.clone())
+        .add_attribute("rarity", rarity_suffix(rarity)))
+}
+
+// records a burned token of an athlete, only counted for reporting since the caps limit minted tokens
+fn update_burn_count(
+    deps: DepsMut,
+    athlete_id: String,
+    rarity: Rarity,
+) -> Result<(), ContractError> {
+    let mut athlete_info = query_athlete_info(deps.as_ref(), athlete_id.clone())?;
+    *athlete_info.burned.get_mut(rarity) += 1;
+
+    athlete_list(deps.storage).save(athlete_id.as_bytes(), &athlete_info)?;
+
+    Ok(())
+}
+
+// uncounts a burned token of an athlete that is minted back by a refunded upgrade
+fn restore_burn_count(
+    deps: DepsMut,
+    athlete_id: String,
+    rarity: Rarity,
+) -> Result<(), ContractError> {
+    let mut athlete_info = query_athlete_info(deps.as_ref(), athlete_id.clone())?;
+    let burned = athlete_info.burned.get_mut(rarity);
+    *burned = burned.saturating_sub(1);
+
+    athlete_list(deps.storage).save(athlete_id.as_bytes(), &athlete_info)?;
+
+    Ok(())
+}
+
+// returns the ids of the active athletes that can still be minted with the given rarity,
+// restricted to the athletes eligible for the pack if one is given. Tokens drawn but not
+// minted yet count against the caps
+fn query_mintable_athletes(
+    deps: Deps,
+    rarity: Rarity,
+    pack: Option<&PackInfo>,
+    drawn: &[DrawnToken],
+) -> StdResult<Vec<u64>> {
+    let contract_info = query_contract_info(deps)?;
+    let athlete_count = query_athlete_count(deps)?;
+    let mut mintable_list = vec![];
+    for n in 0..athlete_count {
+        let athlete_id = n.to_string();
+        if pack.map_or(false, |p| !p.is_eligible(&athlete_id)) {
+            continue;
+        }
+        let athlete_info = query_athlete_info(deps, athlete_id.clone())?;
+        if athlete_info.is_retired {
+            continue;
+        }
+        let pending = drawn.iter().filter(|t| t.athlete_id == athlete_id && t.rarity == rarity).count() as u64;
+        if *athlete_info.minted.get(rarity) + pending < athlete_info.cap(&contract_info, rarity) {
+            mintable_list.push(n);
+        }
+    }
+
+    Ok(mintable_list)
+}
+
+// returns the sampled rarity and its mintable athletes. If the rarity is exhausted, 
+// falls back to the next lower rarities, then to the higher ones
+fn query_fallback_mintable(
+    deps: Deps,
+    rarity: Rarity,
+    pack: &PackInfo,
+    drawn: &[DrawnToken],
+) -> StdResult<Option<(Rarity, Vec<u64>)>> {
+    let position = Rarity::ALL.iter().position(|r| *r == rarity).unwrap_or(0);
+    let fallback_order = Rarity::ALL[..=position]
+        .iter()
+        .rev()
+        .chain(Rarity::ALL[position + 1..].iter());
+
+    for fallback in fallback_order {
+        let mintable_list = query_mintable_athletes(deps, *fallback, Some(pack), drawn)?;
+        if !mintable_list.is_empty() {
+            return Ok(Some((*fallback, mintable_list)));
+        }
+    }
+
+    Ok(None)
+}
+
+// checks that the pack odds are not empty, use each rarity once and have a positive total weight
+fn validate_pack_odds(odds: &[RarityOdds]) -> Result<(), ContractError> {
+    let total_weight: u64 = odds.iter().map(|o| o.weight).sum();
+    if total_weight == 0 {
+        return Err(ContractError::InvalidOdds {});
+    }
+
+    for (i, rarity_odds) in odds.iter().enumerate() {
+        if odds[..i].iter().any(|o| o.rarity == rarity_odds.rarity) {
+            return Err(ContractError::InvalidOdds {});
+        }
+    }
+
+    Ok(())
+}
+
+// checks that a pack type has a name, positive prices in unique denoms and accepted tokens,
+// a positive slot count, valid odds, a sale window ending after it starts and only existing athletes
+fn validate_pack_type(deps: Deps, pack_type: &PackType) -> Result<(), ContractError> {
+    if pack_type.name.is_empty() || pack_type.prices.is_empty() || pack_type.pack_len == 0 {
+        return Err(ContractError::InvalidPackType {});
+    }
+
+    let accepted_tokens = query_contract_info(deps)?.accepted_tokens;
+    for (i, price) in pack_type.prices.iter().enumerate() {
+        if price.amount.is_zero() || pack_type.prices[..i].iter().any(|p| p.denom == price.denom) {
+            return Err(ContractError::InvalidPackType {});
+        }
+        if let Denom::Cw20(contract_addr) = &price.denom {
+            if !accepted_tokens.contains(contract_addr) {
+                return Err(ContractError::UnacceptedToken { token: contract_addr.to_string() });
+            }
+        }
+    }
+
+    validate_pack_odds(&pack_type.odds)?;
+
+    if let (Some(start), Some(end)) = (pack_type.start_time, pack_type.end_time) {
+        if start >= end {
+            return Err(ContractError::InvalidPackType {});
+        }
+    }
+
+    if let Some(athlete_ids) = &pack_type.athlete_ids {
+        if athlete_ids.is_empty() {
+            return Err(ContractError::InvalidPackType {});
+        }
+        let athletes = athlete_list_read(deps.storage);
+        for athlete_id in athlete_ids {
+            if athletes.may_load(athlete_id.as_bytes())?.is_none() {
+                return Err(ContractError::InvalidPackType {});
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// checks that sale phases sorted by start time have unique names, 32 byte hex Merkle roots
+// and time windows that end after they start without overlapping the next one
+fn validate_sale_phases(phases: &[SalePhase]) -> Result<(), ContractError> {
+    for (i, phase) in phases.iter().enumerate() {
+        if phase.name.is_empty() ||
+            phase.start_time >= phase.end_time ||
+            phases[..i].iter().any(|p| p.name == phase.name)
+        {
+            return Err(ContractError::InvalidSalePhases {});
+        }
+
+        if i > 0 && phases[i - 1].end_time > phase.start_time {
+            return Err(ContractError::InvalidSalePhases {});
+        }
+
+        if let Some(merkle_root) = &phase.merkle_root {
+            let mut root_buf: [u8; 32] = [0; 32];
+            if hex::decode_to_slice(merkle_root, &mut root_buf).is_err() {
+                return Err(ContractError::InvalidSalePhases {});
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_reveal_timeout(reveal_timeout: u64) -> Result<(), ContractError> {
+    if reveal_timeout < MIN_REVEAL_TIMEOUT {
+        return Err(ContractError::InvalidRevealTimeout { min: MIN_REVEAL_TIMEOUT });
+    }
+
+    Ok(())
+}
+
+fn validate_deposit_share(deposit_share: Decimal) -> Result<(), ContractError> {
+    if deposit_share > Decimal::one() {
+        return Err(ContractError::InvalidDepositShare {});
+    }
+
+    Ok(())
+}
+
+// checks that lock durations have unique names and last at least a second
+fn validate_game_rules(game_rules: &GameRules) -> Result<(), ContractError> {
+    for (i, lock_duration) in game_rules.lock_durations.iter().enumerate() {
+        if lock_duration.seconds == 0 ||
+            game_rules.lock_durations[..i].iter().any(|d| d.name == lock_duration.name)
+        {
+            return Err(ContractError::InvalidGameRules {});
+        }
+    }
+
+    if game_rules.upgrade_fee.as_ref().map_or(false, |fee| fee.amount.is_zero()) {
+        return Err(ContractError::InvalidGameRules {});
+    }
+
+    if Rarity::ALL.iter().any(|&rarity| game_rules.multipliers.get(rarity).is_zero()) {
+        return Err(ContractError::InvalidGameRules {});
+    }
+
+    Ok(())
+}
+
+// checks that a recipe has a name, a positive input count, an output rarity higher
+// than its inputs and a positive fee
+fn validate_recipe(recipe: &Recipe) -> Result<(), ContractError> {
+    if recipe.name.is_empty() ||
+        recipe.input_count == 0 ||
+        recipe.output_rarity as usize <= recipe.input_rarity as usize ||
+        recipe.fee.as_ref().map_or(false, |fee| fee.amount.is_zero())
+    {
+        return Err(ContractError::InvalidRecipe {});
+    }
+
+    Ok(())
+}
+
+// checks that the inputs of an upgrade are distinct unlocked tokens of the sender
+// matching the count, rarity and athlete of the recipe. Returns their extensions
+fn validate_recipe_inputs(
+    deps: Deps,
+    env: &Env,
+    sender: &Addr,
+    recipe: &Recipe,
+    tokens: &[String],
+) -> Result<Vec<TokenExtension>, ContractError> {
+    if tokens.len() != recipe.input_count as usize {
+        return Err(ContractError::WrongInputCount { expected: recipe.input_count });
+    }
+
+    let mut inputs: Vec<TokenExtension> = vec![];
+    // Operator approval is only queried if a token is not approved on its own
+    let mut is_operator = None;
+    for (i, token_id) in tokens.iter().enumerate() {
+        if tokens[..i].contains(token_id) {
+            return Err(ContractError::DuplicateToken { token_id: token_id.clone() });
+        }
+
+        let token = query_all_token_info(deps, token_id.clone())?;
+        if token.access.owner != sender.as_str() {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        // The burn would otherwise fail later inside the token contract with a generic error
+        if !token.access.approvals.iter().any(|approval| approval.spender == env.contract.address.as_str()) {
+            if is_operator.is_none() {
+                is_operator = Some(query_is_operator(deps, sender, &env.contract.address)?);
+            }
+            if is_operator != Some(true) {
+                return Err(ContractError::BurnNotApproved { token_id: token_id.clone() });
+            }
+        }
+
+        let extension = token.info.extension;
+        if extension.is_locked &&
+            extension.unlock_date.map_or(true, |unlock_date| env.block.time < unlock_date)
+        {
+            return Err(ContractError::TokenInUse { token_id: token_id.clone() });
+        }
+        if extension.rarity != recipe.input_rarity {
+            return Err(ContractError::WrongInputRarity { token_id: token_id.clone() });
+        }
+        if recipe.same_athlete && inputs.first().map_or(false, |first| first.athlete_id != extension.athlete_id) {
+            return Err(ContractError::AthleteMismatch { token_id: token_id.clone() });
+        }
+
+        inputs.push(extension);
+    }
+
+    Ok(inputs)
+}
+
+// checks that the payee addresses are valid and the total weight is positive
+fn validate_revenue_split(api: &dyn Api, revenue_split: &RevenueSplit) -> Result<(), ContractError> {
+    api.addr_validate(revenue_split.treasury.address.as_str())?;
+    for payee in revenue_split.royalties.iter() {
+        api.addr_validate(payee.address.as_str())?;
+    }
+
+    if revenue_split.total_weight() == 0 {
+        return Err(ContractError::InvalidRevenueSplit {});
+    }
+
+    Ok(())
+}
+
+// checks that a contest has a name, a positive lineup size, an entry window closing before
+// the contest ends, positive fee and prizes in the same accepted denom and positive payouts
+// that do not pay more than the prize pool
+fn validate_contest(deps: Deps, contest: &ContestMsg) -> Result<(), ContractError> {
+    if contest.name.is_empty() ||
+        contest.lineup_size == 0 ||
+        contest.max_entries == 0 ||
+        contest.max_entries > MAX_CONTEST_ENTRIES ||
+        contest.entry_start >= contest.entry_end ||
+        contest.entry_end > contest.end_time
+    {
+        return Err(ContractError::InvalidContest {});
+    }
+
+    let accepted_tokens = query_contract_info(deps)?.accepted_tokens;
+    for price in contest.entry_fee.iter().chain(contest.prize_pool.iter()) {
+        if price.amount.is_zero() {
+            return Err(ContractError::InvalidContest {});
+        }
+        if let Denom::Cw20(contract_addr) = &price.denom {
+            if !accepted_tokens.contains(contract_addr) {
+                return Err(ContractError::UnacceptedToken { token: contract_addr.to_string() });
+            }
+        }
+    }
+
+    if let (Some(fee), Some(prize_pool)) = (&contest.entry_fee, &contest.prize_pool) {
+        if fee.denom != prize_pool.denom {
+            return Err(ContractError::InvalidContest {});
+        }
+    }
+
+    let mut payout_total = Decimal::zero();
+    for payout in contest.payouts.iter() {
+        if payout.is_zero() {
+            return Err(ContractError::InvalidContest {});
+        }
+        payout_total = payout_total + *payout;
+    }
+    if payout_total > Decimal::one() {
+        return Err(ContractError::InvalidContest {});
+    }
+
+    validate_lineup_rules(deps, &contest.rules, contest.lineup_size)
+}
+
+// checks that the limits of lineup rules are positive, that position quotas are unique 
+// and can all be met within the lineup size and that eligible athletes exist
+fn validate_lineup_rules(deps: Deps, rules: &LineupRules, lineup_size: u32) -> Result<(), ContractError> {
+    if rules.max_per_athlete == Some(0) ||
+        rules.salary_cap.as_ref().map_or(false, |salary_cap| salary_cap.budget == 0)
+    {
+        return Err(ContractError::InvalidLineupRules {});
+    }
+
+    let mut min_total = 0;
+    for (i, quota) in rules.position_quotas.iter().enumerate() {
+        if quota.position.is_empty() ||
+            quota.min > quota.max ||
+            rules.position_quotas[..i].iter().any(|q| q.position == quota.position)
+        {
+            return Err(ContractError::InvalidLineupRules {});
+        }
+        min_total += quota.min;
+    }
+    if min_total > lineup_size {
+        return Err(ContractError::InvalidLineupRules {});
+    }
+
+    if let Some(athlete_ids) = &rules.eligible_athletes {
+        if athlete_ids.is_empty() {
+            return Err(ContractError::InvalidLineupRules {});
+        }
+        let athletes = athlete_list_read(deps.storage);
+        for athlete_id in athlete_ids {
+            if athletes.may_load(athlete_id.as_bytes())?.is_none() {
+                return Err(ContractError::InvalidLineupRules {});
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// checks the athletes, positions and rarities of a lineup against the rules of a contest
+fn validate_lineup(deps: Deps, rules: &LineupRules, lineup: &[TokenExtension]) -> Result<(), ContractError> {
+    let athletes = athlete_list_read(deps.storage);
+    let mut positions = vec![];
+
+    for (i, token) in lineup.iter().enumerate() {
+        if let Some(eligible_athletes) = &rules.eligible_athletes {
+            if !eligible_athletes.contains(&token.athlete_id) {
+                return Err(ContractError::IneligibleAthlete { athlete_id: token.athlete_id.clone() });
+            }
+        }
+
+        if let Some(max) = rules.max_per_athlete {
+            let count = lineup[..=i].iter().filter(|t| t.athlete_id == token.athlete_id).count();
+            if count > max as usize {
+                return Err(ContractError::AthleteLimitExceeded {
+                    athlete_id: token.athlete_id.clone(),
+                    max,
+                });
+            }
+        }
+
+        if !rules.position_quotas.is_empty() {
+            positions.push(athletes.load(token.athlete_id.as_bytes())?.metadata.position);
+        }
+    }
+
+    for quota in rules.position_quotas.iter() {
+        let count = positions
+            .iter()
+            .filter(|position| position.as_deref() == Some(quota.position.as_str()))
+            .count() as u32;
+
+        if count > quota.max {
+            return Err(ContractError::PositionQuotaExceeded {
+                position: quota.position.clone(),
+                max: quota.max,
+            });
+        }
+        if count < quota.min {
+            return Err(ContractError::PositionQuotaNotMet {
+                position: quota.position.clone(),
+                min: quota.min,
+            });
+        }
+    }
+
+    if let Some(salary_cap) = &rules.salary_cap {
+        let cost: u64 = lineup.iter().map(|token| salary_cap.costs.get(token.rarity)).sum();
+        if cost > salary_cap.budget {
+            return Err(ContractError::SalaryCapExceeded { cost, budget: salary_cap.budget });
+        }
+    }
+
+    Ok(())
+}
+
+// multiplies two decimals, e.g. fantasy points by a rarity multiplier
+fn decimal_mul(a: Decimal, b: Decimal) -> Decimal {
+    Decimal::from_ratio(Uint128::new(a.numerator()) * b, DECIMAL_FRACTION)
+}
+
+// returns the first drand round that will be published after the given time, 
+// offset by DRAND_NEXT_ROUND_SECURITY so it cannot be known when the pack is bought
+fn next_drand_round(time: Timestamp) -> u64 {
+    let from_genesis = time.seconds().saturating_sub(DRAND_GENESIS_TIME);
+    from_genesis / DRAND_PERIOD + 1 + DRAND_NEXT_ROUND_SECURITY
+}
+
+// returns the first serial after the given one whose "<symbol>-<rarity suffix>-<serial>" ID
+// is not held by the token contract, along with the ID. Serials only go up, but the token
+// contract may already hold an ID, e.g. after switching to a contract with existing tokens
+fn next_token_id(
+    deps: Deps,
+    athlete_id: &str,
+    rarity: Rarity,
+    last_serial: u64,
+) -> Result<(u64, String), ContractError> {
+    let symbol = query_athlete_info(deps, athlete_id.to_string())?.symbol;
+
+    let mut token_id = String::new();
+    for serial in last_serial + 1..=last_serial + MAX_TOKEN_ID_ATTEMPTS {
+        token_id = format!(
+            "{}{sep}{}{sep}{}",
+            symbol,
+            rarity_suffix(rarity),
+            serial,
+            sep = TOKEN_ID_SEPARATOR,
+        );
+        if query_token_owner(deps, token_id.clone()).is_err() {
+            return Ok((serial, token_id));
+        }
+    }
+
+    Err(ContractError::TokenIdTaken { token_id })
+}
+
+fn query_decode_token_id(
+    deps: Deps,
+    token_id: String,
+) -> StdResult<TokenIdResponse> {
+    let invalid = || StdError::generic_err(format!("Invalid token ID: {}", token_id));
+
+    let mut parts = token_id.split(TOKEN_ID_SEPARATOR);
+    let (symbol, suffix, serial) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(symbol), Some(suffix), Some(serial), None) => (symbol, suffix, serial),
+        _ => return Err(invalid()),
+    };
+    let rarity = rarity_from_suffix(suffix).ok_or_else(invalid)?;
+    let serial = serial.parse::<u64>().map_err(|_| invalid())?;
+    let athlete_id = ATHLETE_SYMBOLS.may_load(deps.storage, symbol)?.ok_or_else(invalid)?;
+
+    Ok(TokenIdResponse {
+        athlete_id: athlete_id.to_string(),
+        rarity,
+        serial,
+    })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::ContractInfo {} => to_binary(&query_contract_info(deps)?),
+        QueryMsg::PackTypes {} => to_binary(&query_pack_types(deps, env)?),
+        QueryMsg::PackType {
+            name
+        } => to_binary(&query_pack_type(deps, name)?),
+        QueryMsg::SalePhases {} => to_binary(&query_sale_phases(deps, env)?),
+        QueryMsg::RemainingAllocation {
+            phase,
+            address,
+        } => to_binary(&query_remaining_allocation(deps, phase, address)?),
+        QueryMsg::GameRules {} => to_binary(&query_game_rules(deps)?),
+        QueryMsg::RevenueSplit {} => to_binary(&REVENUE_SPLIT.load(deps.storage)?),
+        QueryMsg::Claimable {
+            address
+        } => to_binary(&query_claimable(deps, address)?),
+        QueryMsg::TotalDeposit {} => to_binary(&query_total_deposit(deps)?),
+        QueryMsg::Funds {} => to_binary(&query_funds(deps)?),
+        QueryMsg::YieldInfo {} => to_binary(&query_yield_info(deps)?),
+        QueryMsg::AthleteInfo {
+            athlete_id
+        } => to_binary(&query_athlete_info(deps, athlete_id)?),
+        QueryMsg::AthleteCount {} => to_binary(&query_athlete_count(deps)?),
+        QueryMsg::ListAthletes {
+            start_after,
+            limit,
+        } => to_binary(&query_list_athletes(deps, start_after, limit)?),
+        QueryMsg::AthleteBySymbol {
+            symbol
+        } => to_binary(&query_athlete_by_symbol(deps, symbol)?),
+        QueryMsg::IsTokenMintable {
+            athlete_id,
+            rarity,
+        } => to_binary(&query_token_mintable(deps, athlete_id, rarity)?),
+        QueryMsg::AthleteSupply {
+            athlete_id
+        } => to_binary(&query_athlete_supply(deps, athlete_id)?),
+        QueryMsg::Recipes {} => to_binary(&query_recipes(deps)?),
+        QueryMsg::Recipe {
+            name
+        } => to_binary(&RECIPES.load(deps.storage, &name)?),
+        QueryMsg::DecodeTokenId {
+            token_id
+        } => to_binary(&query_decode_token_id(deps, token_id)?),
+        QueryMsg::LockedTokens {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query_locked_tokens(deps, owner, start_after, limit)?),
+        QueryMsg::CanUnlockToken {
+            token_id
+        } => to_binary(&query_unlock_token(deps, env, token_id)?),
+        QueryMsg::CanUseToken {
+            token_id
+        } => to_binary(&query_use_token(deps, token_id)?),
+        QueryMsg::PackInfo {
+            pack_id
+        } => to_binary(&query_pack_info(deps, pack_id)?),
+        QueryMsg::PendingPacks {
+            buyer,
+            start_after,
+            limit,
+        } => to_binary(&query_pending_packs(deps, buyer, start_after, limit)?),
+        QueryMsg::Upgrade {
+            upgrade_id
+        } => to_binary(&upgrades().load(deps.storage, U64Key::from(upgrade_id))?),
+        QueryMsg::PendingUpgrades {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query_pending_upgrades(deps, owner, start_after, limit)?),
+        QueryMsg::AthleteScore {
+            athlete_id,
+            game_week,
+        } => to_binary(&SCORES.load(deps.storage, (athlete_id.as_str(), U64Key::from(game_week)))?),
+        QueryMsg::AthleteScores {
+            athlete_id,
+            start_after,
+            limit,
+        } => to_binary(&query_athlete_scores(deps, athlete_id, start_after, limit)?),
+        QueryMsg::Contest {
+            contest_id
+        } => to_binary(&CONTESTS.load(deps.storage, U64Key::from(contest_id))?),
+        QueryMsg::ContestRules {
+            contest_id
+        } => to_binary(&CONTESTS.load(deps.storage, U64Key::from(contest_id))?.rules),
+        QueryMsg::Contests {
+            start_after,
+            limit,
+        } => to_binary(&query_contests(deps, start_after, limit)?),
+        QueryMsg::ContestEntries {
+            contest_id,
+            start_after,
+            limit,
+        } => to_binary(&query_contest_entries(deps, contest_id, start_after, limit)?),
+        QueryMsg::Leaderboard {
+            contest_id,
+            start_after,
+            limit,
+        } => to_binary(&query_leaderboard(deps, contest_id, start_after, limit)?),
+        QueryMsg::Lineups {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query_lineups(deps, owner, start_after, limit)?),
+    }
+}
+
+fn query_contract_info(
+    deps: Deps,
+) -> StdResult<ContractInfoResponse> {
+    CONTRACT_INFO.load(deps.storage)
+}
+
+fn query_total_deposit(
+    deps: Deps,
+) -> StdResult<Uint128> {
+    total_deposit(deps.storage)
+}
+
+fn query_yield_info(deps: Deps) -> StdResult<YieldInfoResponse> {
+    let yield_source = query_contract_info(deps)?.yield_source;
+    let exchange_rate = yield_source.exchange_rate(deps)?;
+    let principal = total_deposit(deps.storage)?;
+    let shares = yield_shares(deps.storage)?;
+    let current_value = shares * exchange_rate;
+
+    Ok(YieldInfoResponse {
+        yield_source,
+        principal,
+        shares,
+        exchange_rate,
+        current_value,
+        accrued_yield: current_value.saturating_sub(principal),
+    })
+}
+
+fn query_funds(deps: Deps) -> StdResult<FundsResponse> {
+    let prize_pool = PRIZE_POOL
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, funds)| funds))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    // The treasury balances hold the operating funds, e.g. the principal redeemed from the yield source
+    let treasury = REVENUE_SPLIT.load(deps.storage)?.treasury.address;
+
+    Ok(FundsResponse {
+        principal: query_total_deposit(deps)?,
+        operating: query_claimable(deps, treasury.to_string())?,
+        prize_pool,
+    })
+}
+
+// returns the non zero balances owed to an address
+fn query_claimable(deps: Deps, address: String) -> StdResult<Vec<Price>> {
+    let addr = deps.api.addr_validate(&address)?;
+
+    CLAIMABLE
+        .prefix(&addr)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, balance)| balance))
+        .filter(|balance| balance.as_ref().map_or(true, |b| !b.amount.is_zero()))
+        .collect()
+}
+
+fn query_pack_type(deps: Deps, name: String) -> StdResult<PackTypeResponse> {
+    Ok(PackTypeResponse {
+        pack_type: PACK_TYPES.load(deps.storage, &name)?,
+        sold: PACK_TYPE_SALES.may_load(deps.storage, &name)?.unwrap_or_default(),
+    })
+}
+
+// returns the pack types within their sale window that are not sold out
+fn query_pack_types(deps: Deps, env: Env) -> StdResult<Vec<PackTypeResponse>> {
+    let pack_types = PACK_TYPES
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (_, pack_type) = item?;
+            let sold = PACK_TYPE_SALES.may_load(deps.storage, &pack_type.name)?.unwrap_or_default();
+            Ok(PackTypeResponse { pack_type, sold })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(pack_types
+        .into_iter()
+        .filter(|p| {
+            p.pack_type.is_on_sale(env.block.time) &&
+                p.pack_type.supply.map_or(true, |supply| p.sold < supply)
+        })
+        .collect())
+}
+
+fn query_sale_phases(deps: Deps, env: Env) -> StdResult<SalePhasesResponse> {
+    let phases = SALE_PHASES.may_load(deps.storage)?.unwrap_or_default();
+    let active_phase = phases
+        .iter()
+        .find(|phase| phase.is_active(env.block.time))
+        .map(|phase| phase.name.clone());
+
+    Ok(SalePhasesResponse { phases, active_phase })
+}
+
+// returns the sale phase running at the given time, purchases outside of phases are unrestricted
+fn query_active_sale_phase(deps: Deps, time: Timestamp) -> StdResult<Option<SalePhase>> {
+    Ok(SALE_PHASES
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .into_iter()
+        .find(|phase| phase.is_active(time)))
+}
+
+fn query_remaining_allocation(
+    deps: Deps,
+    phase: String,
+    address: String,
+) -> StdResult<AllocationResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let allocation = SALE_PHASES
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .into_iter()
+        .find(|p| p.name == phase)
+        .ok_or_else(|| StdError::not_found("SalePhase"))?
+        .allocation;
+    let used = PHASE_PURCHASES.may_load(deps.storage, (&phase, &addr))?.unwrap_or_default();
+
+    Ok(AllocationResponse {
+        phase,
+        address,
+        allocation,
+        used,
+        remaining: allocation.map(|allocation| allocation.saturating_sub(used)),
+    })
+}
+
+fn query_game_rules(deps: Deps) -> StdResult<GameRules> {
+    GAME_RULES.load(deps.storage)
+}
+
+fn query_athlete_info(
+    deps: Deps,
+    athlete_id: String
+) -> StdResult<AthleteInfo> {
+    athlete_list_read(deps.storage).load(athlete_id.as_bytes())
+}
+
+fn query_athlete_count(
+    deps: Deps,
+) -> StdResult<u64> {
+    Ok(athlete_count(deps.storage)?)
+}
+
+fn query_pack_info(
+    deps: Deps,
+    pack_id: u64,
+) -> StdResult<PackInfo> {
+    packs().load(deps.storage, U64Key::from(pack_id))
+}
+
+fn query_pending_packs(
+    deps: Deps,
+    buyer: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<PackInfo>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+    let buyer_addr = deps.api.addr_validate(&buyer)?;
+
+    packs()
+        .idx
+        .buyer
+        .prefix(buyer_addr)
+        .range(deps.storage, start, None, Order::Ascending)
+        .map(|item| item.map(|(_, pack)| pack))
+        .filter(|pack| match pack {
+            Ok(pack) => pack.status == PackStatus::Pending,
+            Err(_) => true,
+        })
+        .take(limit)
+        .collect()
+}
+
+fn query_pending_upgrades(
+    deps: Deps,
+    owner: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<PendingUpgrade>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+    let owner_addr = deps.api.addr_validate(&owner)?;
+
+    upgrades()
+        .idx
+        .owner
+        .prefix(owner_addr)
+        .range(deps.storage, start, None, Order::Ascending)
+        .map(|item| item.map(|(_, upgrade)| upgrade))
+        .filter(|upgrade| match upgrade {
+            Ok(upgrade) => upgrade.status == UpgradeStatus::Pending,
+            Err(_) => true,
+        })
+        .take(limit)
+        .collect()
+}
+
+fn query_athlete_scores(
+    deps: Deps,
+    athlete_id: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<AthleteScore>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+
+    SCORES
+        .prefix(&athlete_id)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, score)| score))
+        .collect()
+}
+
+fn query_contests(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<Contest>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+
+    CONTESTS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, contest)| contest))
+        .collect()
+}
+
+fn query_contest_entries(
+    deps: Deps,
+    contest_id: u64,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<ContestEntry>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+
+    contest_entries()
+        .idx
+        .contest
+        .prefix(U64Key::from(contest_id))
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, entry)| entry))
+        .collect()
+}
+
+fn query_leaderboard(
+    deps: Deps,
+    contest_id: u64,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<Standing>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+
+    LEADERBOARDS
+        .prefix(U64Key::from(contest_id))
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, standing)| standing))
+        .collect()
+}
+
+fn query_lineups(
+    deps: Deps,
+    owner: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<ContestEntry>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+    let owner_addr = deps.api.addr_validate(&owner)?;
+
+    contest_entries()
+        .idx
+        .owner
+        .prefix(owner_addr)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, entry)| entry))
+        .collect()
+}
+
+fn query_list_athletes(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<AthleteResponse>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as u64;
+    let start = match start_after {
+        Some(athlete_id) => parse_athlete_id(&athlete_id)? + 1,
+        None => 0,
+    };
+    let end = query_athlete_count(deps)?.min(start.saturating_add(limit));
+
+    // Athlete IDs are sequential so they can be listed without iterating the bucket
+    (start..end)
+        .map(|n| Ok(AthleteResponse {
+            athlete_id: n.to_string(),
+            info: query_athlete_info(deps, n.to_string())?,
+        }))
+        .collect()
+}
+
+fn query_athlete_by_symbol(
+    deps: Deps,
+    symbol: String,
+) -> StdResult<AthleteResponse> {
+    let athlete_id = ATHLETE_SYMBOLS.load(deps.storage, &symbol)?.to_string();
+
+    Ok(AthleteResponse {
+        info: query_athlete_info(deps, athlete_id.clone())?,
+        athlete_id,
+    })
+}
+
+fn parse_athlete_id(athlete_id: &str) -> StdResult<u64> {
+    athlete_id
+        .parse::<u64>()
+        .map_err(|_| StdError::parse_err("u64", format!("Invalid athlete ID: {}", athlete_id)))
+}
+
+fn query_token_mintable(
+    deps: Deps,
+    athlete_id: String,
+    rarity: Rarity
+) -> StdResult<bool> {
+    let contract_info = query_contract_info(deps)?;
+    let athlete_info = query_athlete_info(deps, athlete_id)?;
+
+    Ok(*athlete_info.minted.get(rarity) < athlete_info.cap(&contract_info, rarity))
+}
+
+fn query_athlete_supply(
+    deps: Deps,
+    athlete_id: String,
+) -> StdResult<AthleteSupplyResponse> {
+    let contract_info = query_contract_info(deps)?;
+    let athlete_info = query_athlete_info(deps, athlete_id.clone())?;
+
+    let supply = Rarity::ALL
+        .iter()
+        .map(|&rarity| {
+            let cap = athlete_info.cap(&contract_info, rarity);
+            RaritySupply {
+                rarity,
+                cap,
+                minted: *athlete_info.minted.get(rarity),
+                burned: *athlete_info.burned.get(rarity),
+                remaining: cap.saturating_sub(*athlete_info.minted.get(rarity)),
+            }
+        })
+        .collect();
+
+    Ok(AthleteSupplyResponse { athlete_id, supply })
+}
+
+fn query_token_info(
+    deps: Deps,
+    token_id: String
+) -> StdResult<NftInfoResponse> {
+    let token_address = query_contract_info(deps).unwrap().athlete_addr;
+
+    let msg = TokenMsg::NftInfo { token_id: token_id };
+    let wasm = WasmQuery::Smart {
+        contract_addr: token_address.to_string(),
+        msg: to_binary(&msg)?,
+    };
+
+    let token = deps.querier.query::<NftInfoResponse>(&wasm.into())?;
+
+    let nft_info = NftInfoResponse {
+        token_uri: token.token_uri,
+        extension: TokenExtension {
+            athlete_id: token.extension.athlete_id,
+            rarity: token.extension.rarity,
+            is_locked: token.extension.is_locked,
+            unlock_date: token.extension.unlock_date,
+            usage: token.extension.usage,
+        }
+    };
+
+    Ok(nft_info)
+}
+
+fn query_all_token_info(
+    deps: Deps,
+    token_id: String
+) -> StdResult<AllNftInfoResponse<TokenExtension>> {
+    let token_address = query_contract_info(deps)?.athlete_addr;
+
+    let msg = TokenMsg::AllNftInfo { token_id, include_expired: None };
+    let wasm = WasmQuery::Smart {
+        contract_addr: token_address.to_string(),
+        msg: to_binary(&msg)?,
+    };
+
+    deps.querier.query(&wasm.into())
+}
+
+// returns true if the operator can send and burn every token of the owner
+fn query_is_operator(
+    deps: Deps,
+    owner: &Addr,
+    operator: &Addr,
+) -> StdResult<bool> {
+    let token_address = query_contract_info(deps)?.athlete_addr;
+
+    // Operators are paged through since the token contract cannot be asked about a single one
+    let mut start_after = None;
+    loop {
+        let msg = TokenMsg::ApprovedForAll {
+            owner: owner.to_string(),
+            include_expired: None,
+            start_after,
+            limit: Some(MAX_LIMIT),
+        };
+        let wasm = WasmQuery::Smart {
+            contract_addr: token_address.to_string(),
+            msg: to_binary(&msg)?,
+        };
+        let res: ApprovedForAllResponse = deps.querier.query(&wasm.into())?;
+
+        if res.operators.iter().any(|approval| approval.spender == operator.as_str()) {
+            return Ok(true);
+        }
+        if res.operators.len() < MAX_LIMIT as usize {
+            return Ok(false);
+        }
+        start_after = res.operators.last().map(|approval| approval.spender.clone());
+    }
+}
+
+fn query_token_owner(
+    deps: Deps,
+    token_id: String
+) -> StdResult<Addr> {
+    let token_address = query_contract_info(deps)?.athlete_addr;
+
+    let msg = TokenMsg::OwnerOf { token_id, include_expired: None };
+    let wasm = WasmQuery::Smart {
+        contract_addr: token_address.to_string(),
+        msg: to_binary(&msg)?,
+    };
+
+    let res = deps.querier.query::<OwnerOfResponse>(&wasm.into())?;
+    deps.api.addr_validate(&res.owner)
+}
+
+fn query_unlock_token(
+    deps: Deps,
+    env: Env,
+    token_id: String
+) -> StdResult<bool> {
+    let locked_token = locked_tokens().load(deps.storage, &token_id)?;
+
+    Ok(env.block.time >= locked_token.unlock_date)
+}
+
+fn query_recipes(deps: Deps) -> StdResult<Vec<Recipe>> {
+    RECIPES
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, recipe)| recipe))
+        .collect()
+}
+
+fn query_locked_tokens(
+    deps: Deps,
+    owner: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<LockedToken>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let owner_addr = deps.api.addr_validate(&owner)?;
+
+    locked_tokens()
+        .idx
+        .owner
+        .prefix(owner_addr)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, locked_token)| locked_token))
+        .collect()
+}
+
+fn query_use_token(
+    deps: Deps,
+    token_id: String
+) -> StdResult<bool> {
+    let token = query_token_info(deps, token_id.clone()).unwrap();
+    let mut can_use = false;
+
+    if token.extension.usage > 0 {
+        can_use = true;
+    }
+
+    Ok(can_use)
+}
+
+// returns the randomness of a terrand round if it has already been published
+fn query_terrand_round(
+    deps: Deps,
+    round: u64,
+) -> StdResult<Option<Binary>> {
+    let terrand_addr = query_contract_info(deps)?.terrand_addr;
+
+    let msg = TerrandMsg::GetRandomness { round };
+    let wasm = encode_msg_query(
+        to_binary(&msg)?,
+        terrand_addr
+    )?;
+
+    match deps.querier.query::<GetRandomResponse>(&wasm) {
+        Ok(res) if !res.randomness.is_empty() => Ok(Some(res.randomness)),
+        _ => Ok(None),
+    }
+}
+
+// returns the number of the latest round published by terrand
+fn query_latest_round(deps: Deps) -> StdResult<u64> {
+    let terrand_addr = query_contract_info(deps)?.terrand_addr;
+
+    let msg = TerrandMsg::LatestDrand {};
+    let wasm = encode_msg_query(
+        to_binary(&msg)?,
+        terrand_addr
+    )?;
+
+    Ok(wrapper_msg_get_randomness(deps, wasm)?.round)
+}
+
+// derives a list of random numbers from a seed by hashing it together with each index
+fn randomness_to_numbers(
+    randomness: &Binary,
+    count: u64,
+) -> Vec<u64> {
+    (0..count)
+        .map(|i| {
+            let hash = Sha256::digest(&[randomness.as_slice(), &i.to_be_bytes()].concat());
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&hash[0..8]);
+            u64::from_be_bytes(bytes)
+        })
+        .collect()
+}
+
+// returns the message sending a native or CW20 amount to the recipient
+fn payment_msg(recipient: &Addr, price: &Price) -> StdResult<CosmosMsg> {
+    match &price.denom {
+        Denom::Native(denom) => Ok(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![Coin {
+                denom: denom.clone(),
+                amount: price.amount,
+            }],
+        }.into()),
+        Denom::Cw20(contract_addr) => Ok(WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount: price.amount,
+            })?,
+            funds: vec![],
+        }.into()),
+    }
+}
+
+// verifies a Merkle proof of the leaf against a hex encoded root. Leaves are the sha256 
+// hash of the data and each pair of hashes is sorted before being hashed together
+fn verify_merkle_proof(
+    merkle_root: &str,
+    leaf: &[u8],
+    proof: &[String],
+) -> Result<bool, ContractError> {
+    let mut hash: [u8; 32] = Sha256::digest(leaf).into();
+
+    for step in proof {
+        let mut proof_buf: [u8; 32] = [0; 32];
+        hex::decode_to_slice(step, &mut proof_buf)
+            .map_err(|_| StdError::generic_err("Invalid proof hash"))?;
+
+        let mut hashes = [hash, proof_buf];
+        hashes.sort_unstable();
+        hash = Sha256::digest(&hashes.concat()).into();
+    }
+
+    let mut root_buf: [u8; 32] = [0; 32];
+    hex::decode_to_slice(merkle_root, &mut root_buf)
+        .map_err(|_| StdError::generic_err("Invalid Merkle root"))?;
+
+    Ok(hash == root_buf)
+}
+
+// picks a rarity from the weighted odds using a random number
+fn sample_rarity(
+    odds: &[RarityOdds],
+    random_number: u64,
+) -> Rarity {
+    let total_weight: u64 = odds.iter().map(|o| o.weight).sum();
+    let mut point = random_number % total_weight;
+
+    for rarity_odds in odds.iter() {
+        if point < rarity_odds.weight {
+            return rarity_odds.rarity;
+        }
+        point -= rarity_odds.weight;
+    }
+
+    // Unreachable since the point is always lower than the total weight
+    odds[odds.len() - 1].rarity
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi};
+    use cosmwasm_std::{
+        attr, coin, coins, from_slice, Empty, Event, MemoryStorage, OwnedDeps, Querier, QuerierResult, QueryRequest, SystemError,
+        SystemResult,
+    };
+    use cw721::{Approval, Expiration, OwnerOfResponse};
+
+    use crate::msg::{AthleteMsg, LatestRandomResponse};
+    use crate::state::{LockDuration, Payee, PositionQuota, SalaryCap};
+
+    type MockDeps = OwnedDeps<MemoryStorage, MockApi, MockChain>;
+
+    fn mock_deps() -> MockDeps {
+        OwnedDeps {
+            storage: MemoryStorage::default(),
+            api: MockApi::default(),
+            querier: MockChain::default(),
+        }
+    }
+
+    // Querier answering with the state of the token contract and terrand
+    #[derive(Default)]
+    struct MockChain {
+        // owner, approved spenders and extension by token ID
+        tokens: BTreeMap<String, (String, Vec<String>, TokenExtension)>,
+        // operators by owner
+        operators: BTreeMap<String, Vec<String>>,
+        // randomness by published round
+        rounds: BTreeMap<u64, Binary>,
+    }
+
+    impl Querier for MockChain {
+        fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+            let (contract_addr, msg) = match from_slice(bin_request).unwrap() {
+                QueryRequest::<Empty>::Wasm(WasmQuery::Smart { contract_addr, msg }) => (contract_addr, msg),
+                _ => return SystemResult::Err(SystemError::UnsupportedRequest { kind: "mock".to_string() }),
+            };
+            let res = match contract_addr.as_str() {
+                "athlete" => self.query_token(from_binary(&msg).unwrap()),
+                "terrand" => self.query_terrand(from_binary(&msg).unwrap()),
+                _ => None,
+            };
+            SystemResult::Ok(res.map_or(ContractResult::Err("not found".to_string()), ContractResult::Ok))
+        }
+    }
+
+    impl MockChain {
+        fn add_token(&mut self, token_id: &str, owner: &str, extension: TokenExtension) {
+            self.tokens.insert(token_id.to_string(), (owner.to_string(), vec![], extension));
+        }
+
+        fn owner_of(&self, token_id: &str) -> Option<OwnerOfResponse> {
+            self.tokens.get(token_id).map(|(owner, spenders, _)| OwnerOfResponse {
+                owner: owner.clone(),
+                approvals: spenders
+                    .iter()
+                    .map(|spender| Approval { spender: spender.clone(), expires: Expiration::Never {} })
+                    .collect(),
+            })
+        }
+
+        fn query_token(&self, msg: TokenMsg) -> Option<Binary> {
+            match msg {
+                TokenMsg::OwnerOf { token_id, .. } => {
+                    self.owner_of(&token_id).map(|res| to_binary(&res).unwrap())
+                }
+                TokenMsg::AllNftInfo { token_id, .. } => {
+                    let access = self.owner_of(&token_id)?;
+                    let extension = self.tokens[&token_id].2.clone();
+                    Some(to_binary(&AllNftInfoResponse {
+                        access,
+                        info: cw721::NftInfoResponse { token_uri: None, extension },
+                    }).unwrap())
+                }
+                TokenMsg::ApprovedForAll { owner, start_after, .. } => {
+                    let operators = match start_after {
+                        Some(_) => vec![],
+                        None => self.operators.get(&owner).cloned().unwrap_or_default(),
+                    };
+                    Some(to_binary(&ApprovedForAllResponse {
+                        operators: operators
+                            .into_iter()
+                            .map(|spender| Approval { spender, expires: Expiration::Never {} })
+                            .collect(),
+                    }).unwrap())
+                }
+                _ => None,
+            }
+        }
+
+        fn query_terrand(&self, msg: TerrandMsg) -> Option<Binary> {
+            match msg {
+                TerrandMsg::GetRandomness { round } => self.rounds.get(&round).map(|randomness| {
+                    to_binary(&GetRandomResponse {
+                        randomness: randomness.clone(),
+                        worker: "worker".to_string(),
+                    }).unwrap()
+                }),
+                TerrandMsg::LatestDrand {} => self.rounds.iter().next_back().map(|(round, randomness)| {
+                    to_binary(&LatestRandomResponse {
+                        round: *round,
+                        randomness: randomness.clone(),
+                        worker: "worker".to_string(),
+                    }).unwrap()
+                }),
+            }
+        }
+    }
+
+    use super::*;
+
+    // Tree over the leaves "alice", "bob", "carol" and "dave", hashed with sorted pairs
+    const MERKLE_ROOT: &str = "1f2829c665271766c82831f6b472b40aad1ac9d27aa91aa1523f1f84984485f9";
+
+    fn setup_contract(deps: DepsMut) {
+        let msg = InstantiateMsg {
+            stable_denom: "uusd".to_string(),
+            yield_source: YieldSource::HoldFunds {},
+            terrand_addr: "terrand".to_string(),
+            admin_addr: "admin".to_string(),
+            athlete_addr: "athlete".to_string(),
+            marketplace_addr: "marketplace".to_string(),
+            accepted_tokens: vec![],
+            reveal_timeout: MIN_REVEAL_TIMEOUT,
+            deposit_share: Decimal::percent(50),
+            game_rules: GameRules {
+                usage: PerRarity { common: 1, uncommon: 2, rare: 3, legendary: 4 },
+                lock_durations: vec![LockDuration { name: "day".to_string(), seconds: 86400 }],
+                upgrade_fee: None,
+                multipliers: PerRarity {
+                    common: Decimal::one(),
+                    uncommon: Decimal::percent(150),
+                    rare: Decimal::percent(200),
+                    legendary: Decimal::percent(300),
+                },
+            },
+            revenue_split: RevenueSplit {
+                treasury: Payee { address: Addr::unchecked("treasury"), weight: 1 },
+                prize_pool_weight: 1,
+                royalties: vec![],
+                referrer_weight: 0,
+            },
+            scorers: vec!["scorer".to_string()],
+            dispute_period: 3600,
+            caps: PerRarity { common: 100, uncommon: 50, rare: 10, legendary: 1 },
+        };
+        instantiate(deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+    }
+
+    fn add_athletes(deps: DepsMut, symbols: &[&str]) {
+        let athletes = symbols
+            .iter()
+            .map(|symbol| AthleteMsg {
+                symbol: symbol.to_string(),
+                metadata: AthleteMetadata::default(),
+                caps: AthleteCaps::default(),
+            })
+            .collect();
+        execute_add_athlete(deps, mock_env(), mock_info("admin", &[]), athletes).unwrap();
+    }
+
+    // pack type of common tokens sold for 1000uusd
+    fn add_pack_type(deps: DepsMut, name: &str, pack_len: u64) {
+        let pack_type = PackType {
+            name: name.to_string(),
+            prices: vec![Price {
+                denom: Denom::Native("uusd".to_string()),
+                amount: Uint128::new(1000),
+            }],
+            pack_len,
+            odds: vec![RarityOdds { rarity: Rarity::Common, weight: 1 }],
+            supply: None,
+            wallet_limit: None,
+            start_time: None,
+            end_time: None,
+            athlete_ids: None,
+        };
+        execute_add_pack_type(deps, mock_env(), mock_info("admin", &[]), pack_type).unwrap();
+    }
+
+    // ended contest of game week 1 paying 50%, 30%, 10% and 10% of a 1000uusd prize pool
+    fn save_contest(storage: &mut MemoryStorage) {
+        CONTESTS.save(storage, U64Key::from(1), &Contest {
+            contest_id: 1,
+            name: "Week 1".to_string(),
+            entry_start: Timestamp::from_seconds(1),
+            entry_end: Timestamp::from_seconds(2),
+            end_time: Timestamp::from_seconds(3),
+            game_week: 1,
+            lineup_size: 2,
+            rules: LineupRules::default(),
+            entry_fee: None,
+            prize_pool: Some(Price {
+                denom: Denom::Native("uusd".to_string()),
+                amount: Uint128::new(1000),
+            }),
+            payouts: vec![
+                Decimal::percent(50),
+                Decimal::percent(30),
+                Decimal::percent(10),
+                Decimal::percent(10),
+            ],
+            max_entries: 10,
+            entries: 3,
+            settled: false,
+        }).unwrap();
+    }
+
+    fn save_entry(storage: &mut MemoryStorage, entry_id: u64, owner: &str, lineup: &[(&str, Rarity)]) {
+        let lineup = lineup
+            .iter()
+            .enumerate()
+            .map(|(i, (athlete_id, rarity))| LineupSlot {
+                token_id: format!("{}-{}", owner, i),
+                athlete_id: athlete_id.to_string(),
+                rarity: *rarity,
+            })
+            .collect();
+        contest_entries().save(storage, U64Key::from(entry_id), &ContestEntry {
+            entry_id,
+            contest_id: 1,
+            owner: Addr::unchecked(owner),
+            lineup,
+        }).unwrap();
+    }
+
+    fn save_score(storage: &mut MemoryStorage, athlete_id: &str, points: u64, status: ScoreStatus) {
+        SCORES.save(storage, (athlete_id, U64Key::from(1)), &AthleteScore {
+            athlete_id: athlete_id.to_string(),
+            game_week: 1,
+            points: Decimal::from_ratio(points, 1u64),
+            scorer: Addr::unchecked("scorer"),
+            dispute_ends: Timestamp::from_seconds(3),
+            status,
+        }).unwrap();
+    }
+
+    fn bob_proof() -> Vec<String> {
+        vec![
+            "2bd806c97f0e00af1a1fc3328fa763a9269723c8db8fac4f93af71db186d6e90".to_string(),
+            "e1b219f95e3293ebe8f25e39d2b5ba02b7bb68daa4d3a0cc3323761fab6a9efc".to_string(),
+        ]
+    }
+
+    #[test]
+    fn merkle_proof_verifies_leaves() {
+        assert!(verify_merkle_proof(MERKLE_ROOT, b"bob", &bob_proof()).unwrap());
+
+        let carol_proof = vec![
+            "61ea0803f8853523b777d414ace3130cd4d3f92de2cd7ff8695c337d79c2eeee".to_string(),
+            "cb57721dc3aa8df0eef91989560b053a86be98131f45650bd1c3955e0167ef17".to_string(),
+        ];
+        assert!(verify_merkle_proof(MERKLE_ROOT, b"carol", &carol_proof).unwrap());
+    }
+
+    #[test]
+    fn merkle_proof_rejects_wrong_leaf_or_proof() {
+        // leaf not in the tree
+        assert!(!verify_merkle_proof(MERKLE_ROOT, b"mallory", &bob_proof()).unwrap());
+
+        // proof of another leaf
+        assert!(!verify_merkle_proof(MERKLE_ROOT, b"alice", &bob_proof()).unwrap());
+
+        // incomplete proof
+        assert!(!verify_merkle_proof(MERKLE_ROOT, b"bob", &bob_proof()[..1]).unwrap());
+
+        // malformed hashes
+        let mut proof = bob_proof();
+        proof[1] = "not a hash".to_string();
+        assert!(verify_merkle_proof(MERKLE_ROOT, b"bob", &proof).is_err());
+        assert!(verify_merkle_proof("abcd", b"bob", &bob_proof()).is_err());
+    }
+
+    #[test]
+    fn decode_token_id() {
+        let mut deps = mock_dependencies(&[]);
+        ATHLETE_SYMBOLS.save(deps.as_mut().storage, "LBJ", &7).unwrap();
+
+        let res = query_decode_token_id(deps.as_ref(), "LBJ-R-12".to_string()).unwrap();
+        assert_eq!(res, TokenIdResponse {
+            athlete_id: "7".to_string(),
+            rarity: Rarity::Rare,
+            serial: 12,
+        });
+
+        let res = query_decode_token_id(deps.as_ref(), "LBJ-L-1".to_string()).unwrap();
+        assert_eq!(res.rarity, Rarity::Legendary);
+    }
+
+    #[test]
+    fn decode_invalid_token_id() {
+        let mut deps = mock_dependencies(&[]);
+        ATHLETE_SYMBOLS.save(deps.as_mut().storage, "LBJ", &7).unwrap();
+
+        for token_id in [
+            "",
+            "LBJ",
+            "LBJ-R",
+            "LBJR12",
+            "LBJ-R-12-1",
+            "LBJ-X-12",
+            "LBJ-r-12",
+            "LBJ-R-",
+            "LBJ-R-twelve",
+            "LBJ-R--1",
+            "KD-R-12",
+        ].iter() {
+            let err = query_decode_token_id(deps.as_ref(), token_id.to_string()).unwrap_err();
+            assert_eq!(err, StdError::generic_err(format!("Invalid token ID: {}", token_id)));
+        }
+    }
+
+    #[test]
+    fn settle_contest_ranks_entries() {
+        let mut deps = mock_dependencies(&[]);
+        setup_contract(deps.as_mut());
+        save_contest(&mut deps.storage);
+
+        // alice and carol score 10, bob scores 20 thanks to the rare multiplier
+        save_entry(&mut deps.storage, 1, "alice", &[("0", Rarity::Common), ("2", Rarity::Common)]);
+        save_entry(&mut deps.storage, 2, "bob", &[("0", Rarity::Common), ("1", Rarity::Rare)]);
+        save_entry(&mut deps.storage, 3, "carol", &[("1", Rarity::Rare), ("2", Rarity::Common)]);
+        save_score(&mut deps.storage, "0", 10, ScoreStatus::Finalized);
+        save_score(&mut deps.storage, "1", 5, ScoreStatus::Finalized);
+        // did not play
+        save_score(&mut deps.storage, "2", 0, ScoreStatus::Finalized);
+
+        // only the admin can settle
+        let err = execute_settle_contest(deps.as_mut(), mock_env(), mock_info("alice", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        execute_settle_contest(deps.as_mut(), mock_env(), mock_info("admin", &[]), 1).unwrap();
+
+        // highest score first, the tie goes to the earliest entry
+        let expected = [
+            (2, "bob", 20, 500),
+            (1, "alice", 10, 300),
+            (3, "carol", 10, 100),
+        ];
+        for (i, (entry_id, owner, score, prize)) in expected.iter().enumerate() {
+            let rank = i as u64 + 1;
+            let standing = LEADERBOARDS.load(&deps.storage, (U64Key::from(1), U64Key::from(rank))).unwrap();
+            let prize = Price {
+                denom: Denom::Native("uusd".to_string()),
+                amount: Uint128::new(*prize),
+            };
+            assert_eq!(standing, Standing {
+                rank,
+                entry_id: *entry_id,
+                owner: Addr::unchecked(*owner),
+                score: Decimal::from_ratio(*score as u64, 1u64),
+                prize: Some(prize.clone()),
+            });
+            assert_eq!(CLAIMABLE.load(&deps.storage, (&Addr::unchecked(*owner), "uusd")).unwrap(), prize);
+        }
+        assert!(LEADERBOARDS.may_load(&deps.storage, (U64Key::from(1), U64Key::from(4))).unwrap().is_none());
+
+        // the share of the fourth rank goes back to the prize pool
+        assert_eq!(PRIZE_POOL.load(&deps.storage, "uusd").unwrap().amount, Uint128::new(100));
+
+        assert!(CONTESTS.load(&deps.storage, U64Key::from(1)).unwrap().settled);
+        let err = execute_settle_contest(deps.as_mut(), mock_env(), mock_info("admin", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::ContestSettled { contest_id: 1 });
+    }
+
+    #[test]
+    fn settle_contest_requires_final_scores() {
+        let mut deps = mock_dependencies(&[]);
+        setup_contract(deps.as_mut());
+        save_contest(&mut deps.storage);
+
+        save_entry(&mut deps.storage, 1, "alice", &[("0", Rarity::Common), ("1", Rarity::Common)]);
+        save_score(&mut deps.storage, "0", 10, ScoreStatus::Finalized);
+
+        // missing score
+        let err = execute_settle_contest(deps.as_mut(), mock_env(), mock_info("admin", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::ScoreNotFinal { athlete_id: "1".to_string(), game_week: 1 });
+
+        // score still in its dispute period
+        save_score(&mut deps.storage, "1", 5, ScoreStatus::Submitted);
+        let err = execute_settle_contest(deps.as_mut(), mock_env(), mock_info("admin", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::ScoreNotFinal { athlete_id: "1".to_string(), game_week: 1 });
+
+        assert!(!CONTESTS.load(&deps.storage, U64Key::from(1)).unwrap().settled);
+        assert!(LEADERBOARDS.may_load(&deps.storage, (U64Key::from(1), U64Key::from(1))).unwrap().is_none());
+
+        save_score(&mut deps.storage, "1", 5, ScoreStatus::Finalized);
+        execute_settle_contest(deps.as_mut(), mock_env(), mock_info("admin", &[]), 1).unwrap();
+        let standing = LEADERBOARDS.load(&deps.storage, (U64Key::from(1), U64Key::from(1))).unwrap();
+        assert_eq!(standing.score, Decimal::from_ratio(15u64, 1u64));
+    }
+
+    #[test]
+    fn token_ids_skip_taken_ids() {
+        let mut deps = mock_deps();
+        setup_contract(deps.as_mut());
+        add_athletes(deps.as_mut(), &["LBJ"]);
+        let game_rules = query_game_rules(deps.as_ref()).unwrap();
+        let mint = |deps: &mut MockDeps, rarity| {
+            let token = draw_token(deps.as_ref(), "0".to_string(), rarity, &[]).unwrap();
+            let token_id = token.token_id.clone();
+            mint_token(deps.as_mut(), &Addr::unchecked("owner"), token, &game_rules).unwrap();
+            token_id
+        };
+
+        // IDs already held by the token contract, e.g. after switching contracts
+        for token_id in ["LBJ-C-1", "LBJ-C-2"].iter() {
+            deps.querier.add_token(token_id, "someone", TokenExtension::default());
+        }
+
+        assert_eq!(mint(&mut deps, Rarity::Common), "LBJ-C-3");
+        assert_eq!(token_serial(&deps.storage, "0", Rarity::Common).unwrap(), 3);
+        assert_eq!(mint(&mut deps, Rarity::Common), "LBJ-C-4");
+        assert_eq!(mint(&mut deps, Rarity::Rare), "LBJ-R-1");
+
+        // a later collision is skipped as well, also by tokens drawn together
+        deps.querier.add_token("LBJ-C-5", "someone", TokenExtension::default());
+        let first = draw_token(deps.as_ref(), "0".to_string(), Rarity::Common, &[]).unwrap();
+        assert_eq!(first.token_id, "LBJ-C-6");
+        let second = draw_token(deps.as_ref(), "0".to_string(), Rarity::Common, &[first]).unwrap();
+        assert_eq!(second.token_id, "LBJ-C-7");
+        // nothing is saved until the tokens are minted
+        assert_eq!(token_serial(&deps.storage, "0", Rarity::Common).unwrap(), 4);
+        assert_eq!(mint(&mut deps, Rarity::Common), "LBJ-C-6");
+
+        // the search is bounded
+        for serial in 1..=MAX_TOKEN_ID_ATTEMPTS {
+            deps.querier.add_token(&format!("LBJ-U-{}", serial), "someone", TokenExtension::default());
+        }
+        let err = draw_token(deps.as_ref(), "0".to_string(), Rarity::Uncommon, &[]).unwrap_err();
+        assert_eq!(err, ContractError::TokenIdTaken { token_id: format!("LBJ-U-{}", MAX_TOKEN_ID_ATTEMPTS) });
+    }
+
+    #[test]
+    fn refund_pack_that_cannot_be_opened() {
+        let mut deps = mock_deps();
+        setup_contract(deps.as_mut());
+        add_athletes(deps.as_mut(), &["LBJ"]);
+        add_pack_type(deps.as_mut(), "starter", 2);
+
+        let info = mock_info("buyer", &coins(1000, "uusd"));
+        execute_purchase_native(deps.as_mut(), mock_env(), info, "starter".to_string(), None, None).unwrap();
+        let pack = query_pack_info(deps.as_ref(), 1).unwrap();
+
+        // a pack that can still be opened waits for its timeout
+        let err = execute_refund_pack(deps.as_mut(), mock_env(), mock_info("buyer", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::PackNotExpired {});
+
+        // and cannot be refunded once its round is out
+        deps.querier.rounds.insert(pack.round, Binary::from(vec![7u8; 32]));
+        let mut expired = mock_env();
+        expired.block.time = pack.expires_at;
+        let err = execute_refund_pack(deps.as_mut(), expired, mock_info("buyer", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::RoundPublished { round: pack.round });
+
+        // only one more token of the athlete can be minted, the pack needs two
+        let caps = PerRarity { common: Some(1), uncommon: Some(0), rare: Some(0), legendary: Some(0) };
+        execute_update_athlete_caps(deps.as_mut(), mock_env(), mock_info("admin", &[]), "0".to_string(), caps).unwrap();
+        let err = execute_open_pack(deps.as_mut(), mock_env(), mock_info("buyer", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::Capped {});
+
+        // only the buyer gets the refund, right away
+        let err = execute_refund_pack(deps.as_mut(), mock_env(), mock_info("random", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let res = execute_refund_pack(deps.as_mut(), mock_env(), mock_info("buyer", &[]), 1).unwrap();
+        assert_eq!(res.messages, vec![SubMsg::new(BankMsg::Send {
+            to_address: "buyer".to_string(),
+            amount: coins(1000, "uusd"),
+        })]);
+
+        assert_eq!(query_pack_info(deps.as_ref(), 1).unwrap().status, PackStatus::Refunded);
+        assert_eq!(PACK_TYPE_SALES.load(&deps.storage, "starter").unwrap(), 0);
+        assert_eq!(query_athlete_info(deps.as_ref(), "0".to_string()).unwrap().minted.common, 0);
+
+        let err = execute_refund_pack(deps.as_mut(), mock_env(), mock_info("buyer", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::PackNotPending {});
+    }
+
+    #[test]
+    fn purchase_holds_exact_payment_in_escrow() {
+        let mut deps = mock_deps();
+        setup_contract(deps.as_mut());
+        add_athletes(deps.as_mut(), &["LBJ"]);
+        add_pack_type(deps.as_mut(), "starter", 2);
+        let mut pack_type = PACK_TYPES.load(&deps.storage, "starter").unwrap();
+        pack_type.wallet_limit = Some(1);
+        execute_update_pack_type(deps.as_mut(), mock_env(), mock_info("admin", &[]), pack_type).unwrap();
+
+        let purchase = |deps: &mut MockDeps, buyer: &str, funds: &[Coin]| {
+            execute_purchase_native(deps.as_mut(), mock_env(), mock_info(buyer, funds), "starter".to_string(), None, None)
+        };
+        let wrong_payment = ContractError::WrongPayment { pack_type: "starter".to_string() };
+
+        // only the exact price is accepted
+        assert_eq!(purchase(&mut deps, "buyer", &coins(999, "uusd")).unwrap_err(), wrong_payment);
+        assert_eq!(purchase(&mut deps, "buyer", &coins(1000, "uluna")).unwrap_err(), wrong_payment);
+        assert_eq!(purchase(&mut deps, "buyer", &[]).unwrap_err(), wrong_payment);
+        let funds = vec![coin(1000, "uusd"), coin(1, "uluna")];
+        assert_eq!(purchase(&mut deps, "buyer", &funds).unwrap_err(), wrong_payment);
+
+        // the payment stays in the contract until the pack is opened or refunded
+        let res = purchase(&mut deps, "buyer", &coins(1000, "uusd")).unwrap();
+        assert!(res.messages.is_empty());
+        let pack = query_pack_info(deps.as_ref(), 1).unwrap();
+        assert_eq!(pack.status, PackStatus::Pending);
+        assert_eq!(pack.buyer, "buyer");
+        assert_eq!(pack.price, Price { denom: Denom::Native("uusd".to_string()), amount: Uint128::new(1000) });
+        assert_eq!(pack.round, next_drand_round(mock_env().block.time));
+        assert_eq!(pack.expires_at, mock_env().block.time.plus_seconds(MIN_REVEAL_TIMEOUT));
+        assert!(query_claimable(deps.as_ref(), "treasury".to_string()).unwrap().is_empty());
+        assert_eq!(query_total_deposit(deps.as_ref()).unwrap(), Uint128::zero());
+
+        // the wallet limit is per buyer
+        let err = purchase(&mut deps, "buyer", &coins(1000, "uusd")).unwrap_err();
+        assert_eq!(err, ContractError::WalletLimitReached { pack_type: "starter".to_string() });
+        purchase(&mut deps, "other", &coins(1000, "uusd")).unwrap();
+        assert_eq!(PACK_TYPE_SALES.load(&deps.storage, "starter").unwrap(), 2);
+    }
+
+    #[test]
+    fn purchase_with_cw20() {
+        let mut deps = mock_deps();
+        setup_contract(deps.as_mut());
+        add_athletes(deps.as_mut(), &["LBJ"]);
+        add_pack_type(deps.as_mut(), "starter", 1);
+        // tokens have to be accepted by the admin before they can be priced
+        let tokens = vec!["token".to_string()];
+        execute_update_accepted_tokens(deps.as_mut(), mock_env(), mock_info("admin", &[]), tokens).unwrap();
+        let price = Price { denom: Denom::Cw20(Addr::unchecked("token")), amount: Uint128::new(500) };
+        let mut pack_type = PACK_TYPES.load(&deps.storage, "starter").unwrap();
+        pack_type.prices.push(price.clone());
+        execute_update_pack_type(deps.as_mut(), mock_env(), mock_info("admin", &[]), pack_type).unwrap();
+
+        let receive = |amount: u128| Cw20ReceiveMsg {
+            sender: "buyer".to_string(),
+            amount: Uint128::new(amount),
+            msg: to_binary(&ReceiveMsg::PurchasePack {
+                pack_type: "starter".to_string(),
+                proof: None,
+                referrer: None,
+            }).unwrap(),
+        };
+
+        // the amount has to match the price, sent from an accepted token
+        let err = execute_receive(deps.as_mut(), mock_env(), mock_info("token", &[]), receive(499)).unwrap_err();
+        assert_eq!(err, ContractError::WrongPayment { pack_type: "starter".to_string() });
+        let err = execute_receive(deps.as_mut(), mock_env(), mock_info("other", &[]), receive(500)).unwrap_err();
+        assert_eq!(err, ContractError::UnacceptedToken { token: "other".to_string() });
+
+        // the pack is bought by the sender of the tokens, not by the token contract
+        execute_receive(deps.as_mut(), mock_env(), mock_info("token", &[]), receive(500)).unwrap();
+        let pack = query_pack_info(deps.as_ref(), 1).unwrap();
+        assert_eq!(pack.buyer, "buyer");
+        assert_eq!(pack.price, price);
+        assert_eq!(pack.status, PackStatus::Pending);
+    }
+
+    #[test]
+    fn open_pack_splits_revenue_and_claims() {
+        let mut deps = mock_deps();
+        setup_contract(deps.as_mut());
+        add_athletes(deps.as_mut(), &["LBJ", "KD"]);
+        add_pack_type(deps.as_mut(), "starter", 2);
+        let revenue_split = RevenueSplit {
+            treasury: Payee { address: Addr::unchecked("treasury"), weight: 2 },
+            prize_pool_weight: 4,
+            royalties: vec![Payee { address: Addr::unchecked("artist"), weight: 1 }],
+            referrer_weight: 1,
+        };
+        execute_update_revenue_split(deps.as_mut(), mock_env(), mock_info("admin", &[]), revenue_split).unwrap();
+
+        let info = mock_info("buyer", &coins(1000, "uusd"));
+        execute_purchase_native(deps.as_mut(), mock_env(), info, "starter".to_string(), None, Some("friend".to_string())).unwrap();
+        let pack = query_pack_info(deps.as_ref(), 1).unwrap();
+
+        let err = execute_open_pack(deps.as_mut(), mock_env(), mock_info("anyone", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::RoundNotAvailable { round: pack.round });
+        deps.querier.rounds.insert(pack.round, Binary::from(vec![7u8; 32]));
+
+        // anyone can open the pack, its tokens go to the buyer
+        let res = execute_open_pack(deps.as_mut(), mock_env(), mock_info("anyone", &[]), 1).unwrap();
+        let pack = query_pack_info(deps.as_ref(), 1).unwrap();
+        assert_eq!(pack.status, PackStatus::Opened);
+        assert_eq!(pack.token_ids.len(), 2);
+        assert_eq!(res.messages.len(), 2);
+        for (msg, token_id) in res.messages.iter().zip(pack.token_ids.iter()) {
+            match &msg.msg {
+                CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, msg, .. }) => {
+                    assert_eq!(contract_addr, "athlete");
+                    match from_binary(msg).unwrap() {
+                        TokenMsg::Mint { token_id: minted, owner, extension, .. } => {
+                            assert_eq!(&minted, token_id);
+                            assert_eq!(owner, "buyer");
+                            assert_eq!(extension.rarity, Rarity::Common);
+                            assert_eq!(extension.usage, 1);
+                        }
+                        msg => panic!("unexpected token message {:?}", msg),
+                    }
+                }
+                msg => panic!("unexpected message {:?}", msg),
+            }
+        }
+
+        // half of the payment is deposited, the rest is shared by weight
+        let uusd = |amount: u128| Price { denom: Denom::Native("uusd".to_string()), amount: Uint128::new(amount) };
+        assert_eq!(query_total_deposit(deps.as_ref()).unwrap(), Uint128::new(500));
+        assert_eq!(PRIZE_POOL.load(&deps.storage, "uusd").unwrap(), uusd(250));
+        assert_eq!(query_claimable(deps.as_ref(), "artist".to_string()).unwrap(), vec![uusd(62)]);
+        assert_eq!(query_claimable(deps.as_ref(), "friend".to_string()).unwrap(), vec![uusd(62)]);
+        // the treasury also gets the rounding leftovers
+        assert_eq!(query_claimable(deps.as_ref(), "treasury".to_string()).unwrap(), vec![uusd(126)]);
+
+        let err = execute_open_pack(deps.as_mut(), mock_env(), mock_info("anyone", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::PackNotPending {});
+
+        // balances are claimed once
+        let res = execute_claim(deps.as_mut(), mock_env(), mock_info("artist", &[])).unwrap();
+        assert_eq!(res.messages, vec![SubMsg::new(BankMsg::Send {
+            to_address: "artist".to_string(),
+            amount: coins(62, "uusd"),
+        })]);
+        let err = execute_claim(deps.as_mut(), mock_env(), mock_info("artist", &[])).unwrap_err();
+        assert_eq!(err, ContractError::NothingToClaim {});
+        let err = execute_claim(deps.as_mut(), mock_env(), mock_info("buyer", &[])).unwrap_err();
+        assert_eq!(err, ContractError::NothingToClaim {});
+    }
+
+    #[test]
+    fn open_cw20_pack_without_deposit() {
+        let mut deps = mock_deps();
+        setup_contract(deps.as_mut());
+        add_athletes(deps.as_mut(), &["LBJ"]);
+        add_pack_type(deps.as_mut(), "starter", 1);
+        let tokens = vec!["token".to_string()];
+        execute_update_accepted_tokens(deps.as_mut(), mock_env(), mock_info("admin", &[]), tokens).unwrap();
+        let price = Price { denom: Denom::Cw20(Addr::unchecked("token")), amount: Uint128::new(500) };
+        let mut pack_type = PACK_TYPES.load(&deps.storage, "starter").unwrap();
+        pack_type.prices = vec![price];
+        execute_update_pack_type(deps.as_mut(), mock_env(), mock_info("admin", &[]), pack_type).unwrap();
+
+        let receive = Cw20ReceiveMsg {
+            sender: "buyer".to_string(),
+            amount: Uint128::new(500),
+            msg: to_binary(&ReceiveMsg::PurchasePack {
+                pack_type: "starter".to_string(),
+                proof: None,
+                referrer: None,
+            }).unwrap(),
+        };
+        execute_receive(deps.as_mut(), mock_env(), mock_info("token", &[]), receive).unwrap();
+        let pack = query_pack_info(deps.as_ref(), 1).unwrap();
+        deps.querier.rounds.insert(pack.round, Binary::from(vec![7u8; 32]));
+        execute_open_pack(deps.as_mut(), mock_env(), mock_info("buyer", &[]), 1).unwrap();
+
+        // only stable coins are deposited, the tokens are all shared
+        let tokens = |amount: u128| Price { denom: Denom::Cw20(Addr::unchecked("token")), amount: Uint128::new(amount) };
+        assert_eq!(query_total_deposit(deps.as_ref()).unwrap(), Uint128::zero());
+        assert_eq!(PRIZE_POOL.load(&deps.storage, "token").unwrap(), tokens(250));
+        assert_eq!(query_claimable(deps.as_ref(), "treasury".to_string()).unwrap(), vec![tokens(250)]);
+
+        let res = execute_claim(deps.as_mut(), mock_env(), mock_info("treasury", &[])).unwrap();
+        assert_eq!(res.messages, vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "treasury".to_string(),
+                amount: Uint128::new(250),
+            }).unwrap(),
+            funds: vec![],
+        })]);
+    }
+
+    #[test]
+    fn refund_unrevealed_pack_after_timeout() {
+        let mut deps = mock_deps();
+        setup_contract(deps.as_mut());
+        add_athletes(deps.as_mut(), &["LBJ"]);
+        add_pack_type(deps.as_mut(), "starter", 2);
+
+        let info = mock_info("buyer", &coins(1000, "uusd"));
+        execute_purchase_native(deps.as_mut(), mock_env(), info, "starter".to_string(), None, None).unwrap();
+        let pack = query_pack_info(deps.as_ref(), 1).unwrap();
+        deps.querier.rounds.insert(pack.round - 1, Binary::from(vec![7u8; 32]));
+
+        let mut expired = mock_env();
+        expired.block.time = pack.expires_at.minus_seconds(1);
+        let err = execute_refund_pack(deps.as_mut(), expired.clone(), mock_info("buyer", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::PackNotExpired {});
+
+        // terrand never got the round, so the buyer gets the payment back
+        expired.block.time = pack.expires_at;
+        let res = execute_refund_pack(deps.as_mut(), expired.clone(), mock_info("buyer", &[]), 1).unwrap();
+        assert_eq!(res.messages, vec![SubMsg::new(BankMsg::Send {
+            to_address: "buyer".to_string(),
+            amount: coins(1000, "uusd"),
+        })]);
+        assert_eq!(query_pack_info(deps.as_ref(), 1).unwrap().status, PackStatus::Refunded);
+        assert_eq!(WALLET_PACK_SALES.load(&deps.storage, ("starter", &Addr::unchecked("buyer"))).unwrap(), 0);
+        assert!(query_claimable(deps.as_ref(), "treasury".to_string()).unwrap().is_empty());
+
+        // a refunded pack cannot be opened anymore
+        deps.querier.rounds.insert(pack.round, Binary::from(vec![7u8; 32]));
+        let err = execute_open_pack(deps.as_mut(), expired, mock_info("buyer", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::PackNotPending {});
+    }
+
+    // events of an Anchor market execution, the attributes of the market are in the wasm event
+    fn anchor_reply(id: u64, attributes: &[(&str, &str)]) -> Reply {
+        let wasm_event = attributes
+            .iter()
+            .fold(Event::new("wasm").add_attribute("contract_address", "anchor"), |event, (key, value)| {
+                event.add_attribute(*key, *value)
+            });
+        let events = vec![
+            Event::new("message").add_attribute("action", "execute_contract"),
+            Event::new("execute_contract").add_attribute("contract_address", "anchor"),
+            Event::new("transfer").add_attribute("amount", "1uusd"),
+            wasm_event,
+        ];
+        Reply {
+            id,
+            result: ContractResult::Ok(SubMsgExecutionResponse { events, data: None }),
+        }
+    }
+
+    #[test]
+    fn anchor_deposit_reply() {
+        let mut deps = mock_deps();
+        setup_contract(deps.as_mut());
+
+        let msg = anchor_reply(DEPOSIT_REPLY_ID, &[
+            ("action", "deposit_stable"),
+            ("depositor", "cosmos2contract"),
+            ("mint_amount", "950"),
+            ("deposit_amount", "1000"),
+        ]);
+        let res = reply(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(res.attributes[1], attr("deposit_amount", "1000"));
+        assert_eq!(res.attributes[2], attr("aust_amount", "950"));
+        assert_eq!(query_total_deposit(deps.as_ref()).unwrap(), Uint128::new(1000));
+        assert_eq!(yield_shares(&deps.storage).unwrap(), Uint128::new(950));
+
+        // migrated deposits only add shares
+        let msg = anchor_reply(MIGRATION_DEPOSIT_REPLY_ID, &[("mint_amount", "50"), ("deposit_amount", "60")]);
+        reply(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(query_total_deposit(deps.as_ref()).unwrap(), Uint128::new(1000));
+        assert_eq!(yield_shares(&deps.storage).unwrap(), Uint128::new(1000));
+
+        // attributes have to be numbers found in the wasm event
+        let msg = anchor_reply(DEPOSIT_REPLY_ID, &[("deposit_amount", "1000")]);
+        let err = reply(deps.as_mut(), mock_env(), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidAnchorReply { key: "mint_amount".to_string() });
+        let msg = anchor_reply(DEPOSIT_REPLY_ID, &[("mint_amount", "95O"), ("deposit_amount", "1000")]);
+        let err = reply(deps.as_mut(), mock_env(), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidAnchorReply { key: "mint_amount".to_string() });
+        let msg = Reply {
+            id: DEPOSIT_REPLY_ID,
+            result: ContractResult::Ok(SubMsgExecutionResponse {
+                events: vec![Event::new("transfer").add_attribute("mint_amount", "950").add_attribute("deposit_amount", "1000")],
+                data: None,
+            }),
+        };
+        let err = reply(deps.as_mut(), mock_env(), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidAnchorReply { key: "deposit_amount".to_string() });
+
+        // a failed deposit rolls the transaction back
+        let msg = Reply { id: DEPOSIT_REPLY_ID, result: ContractResult::Err("out of gas".to_string()) };
+        let err = reply(deps.as_mut(), mock_env(), msg).unwrap_err();
+        assert_eq!(err, ContractError::AnchorDepositFailed { error: "out of gas".to_string() });
+
+        let msg = Reply { id: 42, result: ContractResult::Err("unknown".to_string()) };
+        let err = reply(deps.as_mut(), mock_env(), msg).unwrap_err();
+        assert_eq!(err, ContractError::Std(StdError::generic_err("Unknown reply ID: 42")));
+
+        assert_eq!(query_total_deposit(deps.as_ref()).unwrap(), Uint128::new(1000));
+        assert_eq!(yield_shares(&deps.storage).unwrap(), Uint128::new(1000));
+    }
+
+    #[test]
+    fn anchor_redeem_reply() {
+        let mut deps = mock_deps();
+        setup_contract(deps.as_mut());
+        let msg = anchor_reply(DEPOSIT_REPLY_ID, &[("mint_amount", "800"), ("deposit_amount", "1000")]);
+        reply(deps.as_mut(), mock_env(), msg).unwrap();
+
+        // half of the shares are worth 600, the 100 above their principal are yield
+        let msg = anchor_reply(REDEEM_REPLY_ID, &[
+            ("action", "redeem_stable"),
+            ("burn_amount", "400"),
+            ("redeem_amount", "600"),
+        ]);
+        let res = reply(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(res.attributes[2], attr("principal_amount", "500"));
+        assert_eq!(res.attributes[3], attr("received_amount", "600"));
+        assert_eq!(query_total_deposit(deps.as_ref()).unwrap(), Uint128::new(500));
+        assert_eq!(yield_shares(&deps.storage).unwrap(), Uint128::new(400));
+        let uusd = |amount: u128| Price { denom: Denom::Native("uusd".to_string()), amount: Uint128::new(amount) };
+        assert_eq!(query_claimable(deps.as_ref(), "treasury".to_string()).unwrap(), vec![uusd(500)]);
+        assert_eq!(PRIZE_POOL.load(&deps.storage, "uusd").unwrap(), uusd(100));
+
+        let msg = anchor_reply(REDEEM_REPLY_ID, &[("burn_amount", "400")]);
+        let err = reply(deps.as_mut(), mock_env(), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidAnchorReply { key: "redeem_amount".to_string() });
+        let msg = anchor_reply(REDEEM_REPLY_ID, &[("burn_amount", "-1"), ("redeem_amount", "600")]);
+        let err = reply(deps.as_mut(), mock_env(), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidAnchorReply { key: "burn_amount".to_string() });
+        let msg = anchor_reply(REDEEM_REPLY_ID, &[("burn_amount", "401"), ("redeem_amount", "600")]);
+        let err = reply(deps.as_mut(), mock_env(), msg).unwrap_err();
+        assert_eq!(err, ContractError::InsufficientDeposit {});
+        let msg = Reply { id: REDEEM_REPLY_ID, result: ContractResult::Err("not enough aUST".to_string()) };
+        let err = reply(deps.as_mut(), mock_env(), msg).unwrap_err();
+        assert_eq!(err, ContractError::AnchorRedeemFailed { error: "not enough aUST".to_string() });
+        let msg = Reply { id: MIGRATION_REDEEM_REPLY_ID, result: ContractResult::Err("not enough aUST".to_string()) };
+        let err = reply(deps.as_mut(), mock_env(), msg).unwrap_err();
+        assert_eq!(err, ContractError::AnchorRedeemFailed { error: "not enough aUST".to_string() });
+
+        assert_eq!(query_total_deposit(deps.as_ref()).unwrap(), Uint128::new(500));
+        assert_eq!(yield_shares(&deps.storage).unwrap(), Uint128::new(400));
+    }
+
+    #[test]
+    #[cfg(feature = "mock-market")]
+    fn migrate_held_funds_to_mock_market() {
+        let mut deps = mock_deps();
+        setup_contract(deps.as_mut());
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("admin", &coins(1000, "uusd"))).unwrap();
+        assert_eq!(yield_shares(&deps.storage).unwrap(), Uint128::new(1000));
+
+        let yield_source = YieldSource::MockMarket { exchange_rate: Decimal::percent(125) };
+        let err = execute_update_yield_source(deps.as_mut(), mock_env(), mock_info("random", &[]), yield_source.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // the held funds are moved at once, the principal is kept
+        let res = execute_update_yield_source(deps.as_mut(), mock_env(), mock_info("admin", &[]), yield_source).unwrap();
+        assert!(res.messages.is_empty());
+        assert_eq!(res.attributes[1], attr("migrated_amount", "1000"));
+        let info = query_yield_info(deps.as_ref()).unwrap();
+        assert_eq!(info.principal, Uint128::new(1000));
+        assert_eq!(info.shares, Uint128::new(800));
+        assert_eq!(info.accrued_yield, Uint128::zero());
+
+        // changing the rate of the mock market accrues yield without moving the shares
+        let yield_source = YieldSource::MockMarket { exchange_rate: Decimal::percent(150) };
+        execute_update_yield_source(deps.as_mut(), mock_env(), mock_info("admin", &[]), yield_source).unwrap();
+        let info = query_yield_info(deps.as_ref()).unwrap();
+        assert_eq!(info.shares, Uint128::new(800));
+        assert_eq!(info.current_value, Uint128::new(1200));
+        assert_eq!(info.accrued_yield, Uint128::new(200));
+
+        // which goes to the prize pool once redeemed
+        execute_redeem(deps.as_mut(), mock_env(), mock_info("admin", &[]), Uint128::new(1200)).unwrap();
+        let uusd = |amount: u128| Price { denom: Denom::Native("uusd".to_string()), amount: Uint128::new(amount) };
+        assert_eq!(query_claimable(deps.as_ref(), "treasury".to_string()).unwrap(), vec![uusd(1000)]);
+        assert_eq!(PRIZE_POOL.load(&deps.storage, "uusd").unwrap(), uusd(200));
+        assert_eq!(query_yield_info(deps.as_ref()).unwrap().shares, Uint128::zero());
+    }
+
+    // athletes 0 to 2 playing forward, guard and guard
+    fn add_positioned_athletes(deps: DepsMut) {
+        let athletes = [("LBJ", "forward"), ("CP3", "guard"), ("SC", "guard")]
+            .iter()
+            .map(|(symbol, position)| AthleteMsg {
+                symbol: symbol.to_string(),
+                metadata: AthleteMetadata { position: Some(position.to_string()), ..AthleteMetadata::default() },
+                caps: AthleteCaps::default(),
+            })
+            .collect();
+        execute_add_athlete(deps, mock_env(), mock_info("admin", &[]), athletes).unwrap();
+    }
+
+    fn token_extension(athlete_id: &str, rarity: Rarity) -> TokenExtension {
+        TokenExtension {
+            athlete_id: athlete_id.to_string(),
+            rarity,
+            ..TokenExtension::default()
+        }
+    }
+
+    #[test]
+    fn lineup_max_per_athlete() {
+        let mut deps = mock_deps();
+        setup_contract(deps.as_mut());
+        add_positioned_athletes(deps.as_mut());
+        let rules = LineupRules { max_per_athlete: Some(1), ..LineupRules::default() };
+
+        let lineup = vec![token_extension("0", Rarity::Common), token_extension("1", Rarity::Common)];
+        validate_lineup(deps.as_ref(), &rules, &lineup).unwrap();
+
+        let lineup = vec![token_extension("0", Rarity::Common), token_extension("1", Rarity::Common), token_extension("0", Rarity::Rare)];
+        let err = validate_lineup(deps.as_ref(), &rules, &lineup).unwrap_err();
+        assert_eq!(err, ContractError::AthleteLimitExceeded { athlete_id: "0".to_string(), max: 1 });
+    }
+
+    #[test]
+    fn lineup_position_quotas() {
+        let mut deps = mock_deps();
+        setup_contract(deps.as_mut());
+        add_positioned_athletes(deps.as_mut());
+        let rules = LineupRules {
+            position_quotas: vec![PositionQuota { position: "guard".to_string(), min: 1, max: 1 }],
+            ..LineupRules::default()
+        };
+
+        // forwards have no quota
+        let lineup = vec![token_extension("0", Rarity::Common), token_extension("0", Rarity::Common), token_extension("1", Rarity::Common)];
+        validate_lineup(deps.as_ref(), &rules, &lineup).unwrap();
+
+        let lineup = vec![token_extension("1", Rarity::Common), token_extension("2", Rarity::Common)];
+        let err = validate_lineup(deps.as_ref(), &rules, &lineup).unwrap_err();
+        assert_eq!(err, ContractError::PositionQuotaExceeded { position: "guard".to_string(), max: 1 });
+
+        let lineup = vec![token_extension("0", Rarity::Common)];
+        let err = validate_lineup(deps.as_ref(), &rules, &lineup).unwrap_err();
+        assert_eq!(err, ContractError::PositionQuotaNotMet { position: "guard".to_string(), min: 1 });
+    }
+
+    #[test]
+    fn lineup_salary_cap() {
+        let mut deps = mock_deps();
+        setup_contract(deps.as_mut());
+        add_positioned_athletes(deps.as_mut());
+        let rules = LineupRules {
+            salary_cap: Some(SalaryCap {
+                budget: 10,
+                costs: PerRarity { common: 1, uncommon: 2, rare: 4, legendary: 8 },
+            }),
+            ..LineupRules::default()
+        };
+
+        let lineup = vec![token_extension("0", Rarity::Legendary), token_extension("1", Rarity::Uncommon)];
+        validate_lineup(deps.as_ref(), &rules, &lineup).unwrap();
+
+        let lineup = vec![token_extension("0", Rarity::Legendary), token_extension("1", Rarity::Rare)];
+        let err = validate_lineup(deps.as_ref(), &rules, &lineup).unwrap_err();
+        assert_eq!(err, ContractError::SalaryCapExceeded { cost: 12, budget: 10 });
+    }
+
+    #[test]
+    fn lineup_eligible_athletes() {
+        let mut deps = mock_deps();
+        setup_contract(deps.as_mut());
+        add_positioned_athletes(deps.as_mut());
+        let rules = LineupRules {
+            eligible_athletes: Some(vec!["0".to_string(), "1".to_string()]),
+            ..LineupRules::default()
+        };
+
+        let lineup = vec![token_extension("0", Rarity::Common), token_extension("1", Rarity::Common)];
+        validate_lineup(deps.as_ref(), &rules, &lineup).unwrap();
+
+        let lineup = vec![token_extension("0", Rarity::Common), token_extension("2", Rarity::Common)];
+        let err = validate_lineup(deps.as_ref(), &rules, &lineup).unwrap_err();
+        assert_eq!(err, ContractError::IneligibleAthlete { athlete_id: "2".to_string() });
+    }
+
+    // recipe burning two common tokens for an uncommon one, the tokens are approved for burning
+    fn recipe_inputs(deps: &mut MockDeps, same_athlete: bool, inputs: &[(&str, &str, TokenExtension)]) -> Recipe {
+        for (token_id, owner, extension) in inputs.iter() {
+            deps.querier.tokens.insert(
+                token_id.to_string(),
+                (owner.to_string(), vec!["cosmos2contract".to_string()], extension.clone()),
+            );
+        }
+
+        Recipe {
+            name: "upgrade".to_string(),
+            input_rarity: Rarity::Common,
+            input_count: inputs.len() as u32,
+            same_athlete,
+            output_rarity: Rarity::Uncommon,
+            fee: None,
+        }
+    }
+
+    fn input_ids(count: usize) -> Vec<String> {
+        (1..=count).map(|serial| format!("LBJ-C-{}", serial)).collect()
+    }
+
+    #[test]
+    fn recipe_inputs_belong_to_sender() {
+        let mut deps = mock_deps();
+        setup_contract(deps.as_mut());
+        let common = token_extension("0", Rarity::Common);
+        let recipe = recipe_inputs(&mut deps, false, &[("LBJ-C-1", "owner", common.clone()), ("LBJ-C-2", "other", common.clone())]);
+        let env = mock_env();
+
+        let err = validate_recipe_inputs(deps.as_ref(), &env, &Addr::unchecked("owner"), &recipe, &input_ids(2)).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // owned tokens still have to be approved for burning
+        deps.querier.add_token("LBJ-C-2", "owner", common);
+        let err = validate_recipe_inputs(deps.as_ref(), &env, &Addr::unchecked("owner"), &recipe, &input_ids(2)).unwrap_err();
+        assert_eq!(err, ContractError::BurnNotApproved { token_id: "LBJ-C-2".to_string() });
+        deps.querier.operators.insert("owner".to_string(), vec!["cosmos2contract".to_string()]);
+        validate_recipe_inputs(deps.as_ref(), &env, &Addr::unchecked("owner"), &recipe, &input_ids(2)).unwrap();
+    }
+
+    #[test]
+    fn recipe_inputs_are_unlocked() {
+        let mut deps = mock_deps();
+        setup_contract(deps.as_mut());
+        let env = mock_env();
+        let locked = TokenExtension {
+            is_locked: true,
+            unlock_date: Some(env.block.time.plus_seconds(1)),
+            ..token_extension("0", Rarity::Common)
+        };
+        let recipe = recipe_inputs(&mut deps, false, &[("LBJ-C-1", "owner", token_extension("0", Rarity::Common)), ("LBJ-C-2", "owner", locked)]);
+
+        let err = validate_recipe_inputs(deps.as_ref(), &env, &Addr::unchecked("owner"), &recipe, &input_ids(2)).unwrap_err();
+        assert_eq!(err, ContractError::TokenInUse { token_id: "LBJ-C-2".to_string() });
+
+        // a lock that has expired no longer holds the token
+        let mut later = mock_env();
+        later.block.time = env.block.time.plus_seconds(1);
+        validate_recipe_inputs(deps.as_ref(), &later, &Addr::unchecked("owner"), &recipe, &input_ids(2)).unwrap();
+    }
+
+    #[test]
+    fn recipe_inputs_have_input_rarity() {
+        let mut deps = mock_deps();
+        setup_contract(deps.as_mut());
+        let recipe = recipe_inputs(&mut deps, false, &[("LBJ-C-1", "owner", token_extension("0", Rarity::Common)), ("LBJ-C-2", "owner", token_extension("0", Rarity::Rare))]);
+        let env = mock_env();
+
+        let err = validate_recipe_inputs(deps.as_ref(), &env, &Addr::unchecked("owner"), &recipe, &input_ids(2)).unwrap_err();
+        assert_eq!(err, ContractError::WrongInputRarity { token_id: "LBJ-C-2".to_string() });
+        let err = validate_recipe_inputs(deps.as_ref(), &env, &Addr::unchecked("owner"), &recipe, &input_ids(1)).unwrap_err();
+        assert_eq!(err, ContractError::WrongInputCount { expected: 2 });
+        let tokens = vec!["LBJ-C-1".to_string(), "LBJ-C-1".to_string()];
+        let err = validate_recipe_inputs(deps.as_ref(), &env, &Addr::unchecked("owner"), &recipe, &tokens).unwrap_err();
+        assert_eq!(err, ContractError::DuplicateToken { token_id: "LBJ-C-1".to_string() });
+    }
+
+    #[test]
+    fn recipe_inputs_of_same_athlete() {
+        let mut deps = mock_deps();
+        setup_contract(deps.as_mut());
+        let inputs = [("LBJ-C-1", "owner", token_extension("0", Rarity::Common)), ("LBJ-C-2", "owner", token_extension("1", Rarity::Common))];
+        let env = mock_env();
+
+        // only same athlete recipes need matching athletes
+        let recipe = recipe_inputs(&mut deps, false, &inputs);
+        validate_recipe_inputs(deps.as_ref(), &env, &Addr::unchecked("owner"), &recipe, &input_ids(2)).unwrap();
+
+        let recipe = recipe_inputs(&mut deps, true, &inputs);
+        let err = validate_recipe_inputs(deps.as_ref(), &env, &Addr::unchecked("owner"), &recipe, &input_ids(2)).unwrap_err();
+        assert_eq!(err, ContractError::AthleteMismatch { token_id: "LBJ-C-2".to_string() });
+    }
+
+    #[test]
+    fn upgrade_fee_is_checked() {
+        let fee = coin(100, "uusd");
+        assert_eq!(check_upgrade_fee("free", &[], None).unwrap(), None);
+        assert_eq!(check_upgrade_fee("paid", &coins(100, "uusd"), Some(&fee)).unwrap(), Some(fee.clone()));
+
+        // funds sent to a recipe without a fee are rejected
+        let err = check_upgrade_fee("free", &coins(100, "uusd"), None).unwrap_err();
+        assert_eq!(err, ContractError::NoUpgradeFee { recipe: "free".to_string() });
+
+        // as are other denoms, even alongside the fee
+        let err = check_upgrade_fee("paid", &coins(100, "uluna"), Some(&fee)).unwrap_err();
+        assert_eq!(err, ContractError::WrongUpgradeDenom { denom: "uusd".to_string() });
+        let err = check_upgrade_fee("paid", &[fee.clone(), coin(1, "uluna")], Some(&fee)).unwrap_err();
+        assert_eq!(err, ContractError::WrongUpgradeDenom { denom: "uusd".to_string() });
+
+        let err = check_upgrade_fee("paid", &coins(99, "uusd"), Some(&fee)).unwrap_err();
+        assert_eq!(err, ContractError::WrongUpgradeFee { fee: "100uusd".to_string() });
+    }
+
+    #[test]
+    fn refund_upgrade_that_cannot_be_revealed() {
+        let mut deps = mock_deps();
+        setup_contract(deps.as_mut());
+        add_athletes(deps.as_mut(), &["LBJ"]);
+        let recipe = Recipe {
+            name: "random".to_string(),
+            input_rarity: Rarity::Common,
+            input_count: 2,
+            same_athlete: false,
+            output_rarity: Rarity::Uncommon,
+            fee: Some(coin(100, "uusd")),
+        };
+        execute_add_recipe(deps.as_mut(), mock_env(), mock_info("admin", &[]), recipe).unwrap();
+
+        let extension = TokenExtension {
+            athlete_id: "0".to_string(),
+            rarity: Rarity::Common,
+            usage: 1,
+            ..TokenExtension::default()
+        };
+        deps.querier.add_token("LBJ-C-1", "owner", extension.clone());
+        deps.querier.add_token("LBJ-C-2", "owner", extension.clone());
+        deps.querier.operators.insert("owner".to_string(), vec!["cosmos2contract".to_string()]);
+
+        let tokens = vec!["LBJ-C-1".to_string(), "LBJ-C-2".to_string()];
+        let info = mock_info("owner", &coins(100, "uusd"));
+        execute_upgrade_token(deps.as_mut(), mock_env(), info, "random".to_string(), tokens.clone()).unwrap();
+        let upgrade = upgrades().load(&deps.storage, U64Key::from(1)).unwrap();
+        assert_eq!(upgrade.fee, Some(coin(100, "uusd")));
+        assert_eq!(query_athlete_info(deps.as_ref(), "0".to_string()).unwrap().burned.common, 2);
+
+        // the round has to be out, and an upgrade that can be revealed is not refunded
+        let err = execute_refund_upgrade(deps.as_mut(), mock_env(), mock_info("owner", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::RoundNotAvailable { round: upgrade.round });
+        deps.querier.rounds.insert(upgrade.round, Binary::from(vec![7u8; 32]));
+        let err = execute_refund_upgrade(deps.as_mut(), mock_env(), mock_info("owner", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::UpgradeRevealable {});
+
+        // no uncommon token can be minted anymore
+        let caps = PerRarity { common: Some(100), uncommon: Some(0), rare: Some(0), legendary: Some(0) };
+        execute_update_athlete_caps(deps.as_mut(), mock_env(), mock_info("admin", &[]), "0".to_string(), caps).unwrap();
+        let err = execute_reveal_upgrade(deps.as_mut(), mock_env(), mock_info("anyone", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::Capped {});
+
+        // only the owner gets the inputs and the fee back
+        let err = execute_refund_upgrade(deps.as_mut(), mock_env(), mock_info("random", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let res = execute_refund_upgrade(deps.as_mut(), mock_env(), mock_info("owner", &[]), 1).unwrap();
+        let mut messages: Vec<SubMsg> = tokens
+            .into_iter()
+            .map(|token_id| SubMsg::new(WasmMsg::Execute {
+                contract_addr: "athlete".to_string(),
+                msg: to_binary(&TokenMsg::Mint {
+                    token_id,
+                    owner: "owner".to_string(),
+                    token_uri: None,
+                    extension: extension.clone(),
+                }).unwrap(),
+                funds: vec![],
+            }))
+            .collect();
+        messages.push(SubMsg::new(BankMsg::Send {
+            to_address: "owner".to_string(),
+            amount: coins(100, "uusd"),
+        }));
+        assert_eq!(res.messages, messages);
+
+        let upgrade = upgrades().load(&deps.storage, U64Key::from(1)).unwrap();
+        assert_eq!(upgrade.status, UpgradeStatus::Refunded);
+        assert_eq!(query_athlete_info(deps.as_ref(), "0".to_string()).unwrap().burned.common, 0);
+
+        let err = execute_refund_upgrade(deps.as_mut(), mock_env(), mock_info("owner", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::UpgradeNotPending {});
+        let err = execute_reveal_upgrade(deps.as_mut(), mock_env(), mock_info("anyone", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::UpgradeNotPending {});
+    }
+}
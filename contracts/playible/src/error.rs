@@ -12,11 +12,11 @@ pub enum ContractError {
     #[error("token_id already claimed")]
     Claimed {},
 
-    #[error("You need to send exactly {}{} to purchase a pack", amount, denom)]
-    WrongAmount { amount: u64, denom: String },
+    #[error("Payment does not match any price of pack type {}", pack_type)]
+    WrongPayment { pack_type: String },
 
-    #[error("The current round has already been used. Please wait for the next round")]
-    UsedRound {},
+    #[error("CW20 token {} is not accepted as payment", token)]
+    UnacceptedToken { token: String },
 
     #[error("Minting cannot exceed the cap")]
     Capped {},
@@ -32,4 +32,181 @@ pub enum ContractError {
 
     #[error("Athlete ID does not exist")]
     DoesNotExist {},
+
+    #[error("Symbol {} is already used by another athlete", symbol)]
+    SymbolClaimed { symbol: String },
+
+    #[error("Symbol {} has to be non-empty and cannot contain '-'", symbol)]
+    InvalidSymbol { symbol: String },
+
+    #[error("Token ID {} already exists in the token contract", token_id)]
+    TokenIdTaken { token_id: String },
+
+    #[error("Pack has already been opened or refunded")]
+    PackNotPending {},
+
+    #[error("Upgrade has already been revealed or refunded")]
+    UpgradeNotPending {},
+
+    #[error("Upgrade can still be revealed, reveal it instead")]
+    UpgradeRevealable {},
+
+    #[error("Terrand round {} has not been published yet", round)]
+    RoundNotAvailable { round: u64 },
+
+    #[error("Terrand has published round {} or a later one, open the pack instead", round)]
+    RoundPublished { round: u64 },
+
+    #[error("Reveal timeout has to be at least {} seconds", min)]
+    InvalidRevealTimeout { min: u64 },
+
+    #[error("Pack cannot be refunded before its reveal timeout")]
+    PackNotExpired {},
+
+    #[error("Pack odds must use each rarity once and have a positive total weight")]
+    InvalidOdds {},
+
+    #[error("Pack type {} already exists", pack_type)]
+    PackTypeExists { pack_type: String },
+
+    #[error("Pack type {} is not on sale", pack_type)]
+    NotOnSale { pack_type: String },
+
+    #[error("Pack type {} is sold out", pack_type)]
+    SoldOut { pack_type: String },
+
+    #[error("Address has reached the purchase limit of pack type {}", pack_type)]
+    WalletLimitReached { pack_type: String },
+
+    #[error("Pack types need unique positive prices in accepted denoms, a positive slot count, a valid sale window and existing athletes")]
+    InvalidPackType {},
+
+    #[error("Sale phases need unique names, valid Merkle roots and non-overlapping time windows")]
+    InvalidSalePhases {},
+
+    #[error("Address is not on the allowlist of sale phase {}", phase)]
+    InvalidProof { phase: String },
+
+    #[error("Address has used its allocation of sale phase {}", phase)]
+    AllocationExceeded { phase: String },
+
+    #[error("Anchor deposit failed: {}", error)]
+    AnchorDepositFailed { error: String },
+
+    #[error("Anchor redemption failed: {}", error)]
+    AnchorRedeemFailed { error: String },
+
+    #[error("Anchor reply is missing the {} attribute", key)]
+    InvalidAnchorReply { key: String },
+
+    #[error("Redeemed amount exceeds the shares held in the yield source")]
+    InsufficientDeposit {},
+
+    #[error("Revenue split needs a positive total weight")]
+    InvalidRevenueSplit {},
+
+    #[error("Buyers cannot refer themselves")]
+    InvalidReferrer {},
+
+    #[error("You need to send exactly {} to upgrade a token", fee)]
+    WrongUpgradeFee { fee: String },
+
+    #[error("Recipe {} has no upgrade fee, no funds should be sent", recipe)]
+    NoUpgradeFee { recipe: String },
+
+    #[error("Upgrade fees are only paid in {}", denom)]
+    WrongUpgradeDenom { denom: String },
+
+    #[error("Nothing to claim")]
+    NothingToClaim {},
+
+    #[error("Contests need a name, a positive lineup size, entries closing before the end, an entry limit between 1 and 200, positive prices in accepted denoms and payouts totalling at most 1")]
+    InvalidContest {},
+
+    #[error("Prize pool cannot cover the contest prizes")]
+    InsufficientPrizePool {},
+
+    #[error("Contest {} is not open for entries", contest_id)]
+    ContestNotOpen { contest_id: u64 },
+
+    #[error("Contest {} has reached its entry limit", contest_id)]
+    ContestFull { contest_id: u64 },
+
+    #[error("Payment does not match the entry fee of contest {}", contest_id)]
+    WrongEntryFee { contest_id: u64 },
+
+    #[error("Lineup must have {} tokens", expected)]
+    InvalidLineupSize { expected: u32 },
+
+    #[error("Token {} appears more than once in the lineup", token_id)]
+    DuplicateToken { token_id: String },
+
+    #[error("Lineup rules need positive limits, unique positions with min quotas below max and existing eligible athletes")]
+    InvalidLineupRules {},
+
+    #[error("Lineup can have at most {} tokens of athlete {}", max, athlete_id)]
+    AthleteLimitExceeded { athlete_id: String, max: u32 },
+
+    #[error("Lineup can have at most {} tokens playing {}", max, position)]
+    PositionQuotaExceeded { position: String, max: u32 },
+
+    #[error("Lineup needs at least {} tokens playing {}", min, position)]
+    PositionQuotaNotMet { position: String, min: u32 },
+
+    #[error("Lineup costs {} points, over the salary cap of {}", cost, budget)]
+    SalaryCapExceeded { cost: u64, budget: u64 },
+
+    #[error("Athlete {} is not eligible for this contest", athlete_id)]
+    IneligibleAthlete { athlete_id: String },
+
+    #[error("Token {} is already locked", token_id)]
+    TokenInUse { token_id: String },
+
+    #[error("Score of athlete {} for game week {} has already been submitted", athlete_id, game_week)]
+    ScoreExists { athlete_id: String, game_week: u64 },
+
+    #[error("Dispute period of the score of athlete {} for game week {} has ended", athlete_id, game_week)]
+    DisputePeriodEnded { athlete_id: String, game_week: u64 },
+
+    #[error("Score of athlete {} for game week {} is still in its dispute period", athlete_id, game_week)]
+    DisputePeriodActive { athlete_id: String, game_week: u64 },
+
+    #[error("Score of athlete {} for game week {} is already final", athlete_id, game_week)]
+    ScoreFinalized { athlete_id: String, game_week: u64 },
+
+    #[error("Contest {} has not ended yet", contest_id)]
+    ContestNotEnded { contest_id: u64 },
+
+    #[error("Contest {} has already been settled", contest_id)]
+    ContestSettled { contest_id: u64 },
+
+    #[error("Score of athlete {} for game week {} is not final yet", athlete_id, game_week)]
+    ScoreNotFinal { athlete_id: String, game_week: u64 },
+
+    #[error("Recipe {} already exists", recipe)]
+    RecipeExists { recipe: String },
+
+    #[error("Recipes need a name, a positive input count, an output rarity higher than the inputs and a positive fee")]
+    InvalidRecipe {},
+
+    #[error("Recipe needs {} input tokens", expected)]
+    WrongInputCount { expected: u32 },
+
+    #[error("Token {} does not have the input rarity of the recipe", token_id)]
+    WrongInputRarity { token_id: String },
+
+    #[error("Token {} is not of the same athlete as the other inputs", token_id)]
+    AthleteMismatch { token_id: String },
+
+    #[error("Contract is not approved to burn token {}", token_id)]
+    BurnNotApproved { token_id: String },
+
+    #[error("Deposit share cannot be greater than 1")]
+    InvalidDepositShare {},
+
+    #[error("Lock duration {} does not exist", duration)]
+    UnknownDuration { duration: String },
+
+    #[error("Game rules need lock durations with unique names lasting at least a second, a positive upgrade fee and positive rarity multipliers")]
+    InvalidGameRules {},
 }
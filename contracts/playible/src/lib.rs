@@ -3,6 +3,8 @@ pub mod msg;
 pub mod state;
 pub mod helpers;
 pub mod querier;
+pub mod anchor;
+pub mod yield_source;
 mod error;
 
 pub use crate::error::ContractError;
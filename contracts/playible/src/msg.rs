@@ -1,16 +1,23 @@
-use cosmwasm_std::{Binary, CanonicalAddr, Uint128, Timestamp};
+use cosmwasm_std::{Binary, Decimal, Uint128, Timestamp};
 use cw20::{Cw20ReceiveMsg};
+use cw721::Cw721ReceiveMsg;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_bignumber::{Uint256, Decimal256};
+use cw721_base::Rarity;
+
+use crate::state::{
+    AthleteCaps, AthleteInfo, AthleteMetadata, GameRules, LineupRules, PackType, PerRarity, Price,
+    Recipe, RevenueSplit, SalePhase,
+};
+use crate::yield_source::YieldSource;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     /// Stable coin denomination. 
     pub stable_denom: String,
-    /// anchor contract address for depositing the rewards
-    pub anchor_addr: String,
+    /// where the deposited share of the pack proceeds earns yield
+    pub yield_source: YieldSource,
     /// terrand contract address for calling Oracle's DRand
     pub terrand_addr: String,
     /// contract admin
@@ -19,15 +26,22 @@ pub struct InstantiateMsg {
     pub athlete_addr: String,
     /// contract address for the Marketplace contract
     pub marketplace_addr: String,
-    /// Number of Player NFTs to be pulled per pack
-    pub pack_len: u64,
-    /// Price of each pack
-    pub pack_price: u64,
-    // Maximum number tokens to be minted for each rarity
-    pub common_cap: u64,
-    pub uncommon_cap: u64,
-    pub rare_cap: u64,
-    pub legendary_cap: u64,
+    /// CW20 token contracts that can be used as pack payment
+    pub accepted_tokens: Vec<String>,
+    /// Seconds after a purchase before an unopened pack can be refunded, at least an hour
+    pub reveal_timeout: u64,
+    /// Share of each opened pack's stable coin payment deposited to the yield source, e.g. "0.5"
+    pub deposit_share: Decimal,
+    /// Token usages per rarity, lock durations and upgrade fee
+    pub game_rules: GameRules,
+    /// Weights of the payees sharing the pack sales and upgrade fees
+    pub revenue_split: RevenueSplit,
+    /// Stat oracle addresses allowed to submit athlete scores
+    pub scorers: Vec<String>,
+    /// Seconds after submission during which the admin can correct a score
+    pub dispute_period: u64,
+    /// Maximum number of tokens ever minted per athlete for each rarity, burning does not free up supply
+    pub caps: PerRarity<u64>,
 }  
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
@@ -35,7 +49,7 @@ pub struct TokenExtension {
     /// Reference ID of the Athlete Token
     pub athlete_id: String,
     /// Describes the rarity of the NFT 
-    pub rarity: String,
+    pub rarity: Rarity,
     /// Determines whether or not the NFT is locked for Fantasy Sports
     pub is_locked: bool,
     /// Determines the unlock date after the NFT has been locked
@@ -44,6 +58,138 @@ pub struct TokenExtension {
     pub usage: u64,
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AthleteMsg {
+    /// Symbol used for token_id generation
+    pub symbol: String,
+    /// Descriptive information about the athlete
+    pub metadata: AthleteMetadata,
+    /// Supply caps overriding the global caps
+    #[serde(default)]
+    pub caps: AthleteCaps,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ContestMsg {
+    pub name: String,
+    /// Lineups can be entered from this time
+    pub entry_start: Timestamp,
+    /// Lineups can be entered until this time
+    pub entry_end: Timestamp,
+    /// Time the contest ends, entered tokens stay locked until then
+    pub end_time: Timestamp,
+    /// Game week of the athlete scores the lineups are ranked by
+    pub game_week: u64,
+    /// Number of tokens in a lineup
+    pub lineup_size: u32,
+    /// Constraints the lineups have to meet
+    #[serde(default)]
+    pub rules: LineupRules,
+    /// Fee paid for each entry, entry is free if unset
+    pub entry_fee: Option<Price>,
+    /// Guaranteed prizes taken from the contract prize pool, which holds the redeemed yield
+    pub prize_pool: Option<Price>,
+    /// Share of the prize pool paid to each rank, starting from the first, e.g. ["0.5", "0.3", "0.2"]
+    pub payouts: Vec<Decimal>,
+    /// Maximum number of lineups that can be entered, at most 200 so the contest can be settled
+    pub max_entries: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ScoreMsg {
+    pub athlete_id: String,
+    pub game_week: u64,
+    /// Fantasy points scored by the athlete during the game week, zero if the athlete did not play
+    pub points: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RaritySupply {
+    pub rarity: Rarity,
+    /// Maximum number of tokens ever minted
+    pub cap: u64,
+    /// Number of tokens minted so far
+    pub minted: u64,
+    /// Number of tokens burned by upgrades
+    pub burned: u64,
+    /// Number of tokens that can still be minted
+    pub remaining: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TokenIdResponse {
+    pub athlete_id: String,
+    pub rarity: Rarity,
+    /// Position of the token among the tokens minted for the athlete and rarity, starting at 1
+    pub serial: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AthleteSupplyResponse {
+    pub athlete_id: String,
+    /// Supply of each rarity, from lowest to highest
+    pub supply: Vec<RaritySupply>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PackTypeResponse {
+    pub pack_type: PackType,
+    /// Number of packs sold so far, refunded packs excluded
+    pub sold: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct FundsResponse {
+    /// Stable coins deposited to the yield source
+    pub principal: Uint128,
+    /// Operating funds per denom, claimable by the treasury
+    pub operating: Vec<Price>,
+    /// Funds kept in the contract for contest prizes per denom
+    pub prize_pool: Vec<Price>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct YieldInfoResponse {
+    pub yield_source: YieldSource,
+    /// Stable coins deposited to the yield source
+    pub principal: Uint128,
+    /// Shares held in the yield source, e.g. aUST for Anchor
+    pub shares: Uint128,
+    /// Stable coin value of a share
+    pub exchange_rate: Decimal,
+    /// Stable coin value of the shares
+    pub current_value: Uint128,
+    /// Interest earned on top of the principal, available for prizes
+    pub accrued_yield: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SalePhasesResponse {
+    /// Sale phases ordered by start time
+    pub phases: Vec<SalePhase>,
+    /// Name of the phase running at the current block time
+    pub active_phase: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AllocationResponse {
+    pub phase: String,
+    pub address: String,
+    /// Maximum number of packs the address can buy during the phase, unlimited if unset
+    pub allocation: Option<u64>,
+    /// Number of packs bought during the phase
+    pub used: u64,
+    /// Number of packs the address can still buy, unlimited if unset
+    pub remaining: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AthleteResponse {
+    /// Athlete ID used by the Athlete tokens
+    pub athlete_id: String,
+    pub info: AthleteInfo,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct NftInfoResponse {
     /// Universal Resource Identifier link of the NFT
@@ -57,24 +203,103 @@ pub struct NftInfoResponse {
 pub enum ExecuteMsg {
     /// For testing stuff
     Test {}, 
-    /// Purchase an athlete token pack
+    /// Purchase an athlete token pack of the given type with native coins, 
+    /// the payment is held until the pack is opened
     PurchasePack {
-        rand_seed: String,
+        pack_type: String,
+        /// Hex encoded Merkle proof of the sender address, required during allowlist phases
+        proof: Option<Vec<String>>,
+        /// Address that referred the buyer
+        referrer: Option<String>,
+    },
+    /// Receives CW20 tokens sent with a `ReceiveMsg` payload
+    Receive(Cw20ReceiveMsg),
+    /// Reveals and mints the athletes of a pack once its terrand round is published.
+    /// Can be called by anyone
+    OpenPack {
+        pack_id: u64,
+    },
+    /// Returns the payment of a pack if terrand has not reached its round before the timeout,
+    /// or right away if the pack cannot be opened since its athletes can no longer be minted.
+    /// Can only be called by the pack buyer
+    RefundPack {
+        pack_id: u64,
     },
     /// Deposit Stablecoins into the contract to receive an athlete token
     DepositStable {},
-    /// Redeem Stablecoins (UST) from Anchor
+    /// Redeem Stablecoins (UST) from the yield source. The principal goes back to the operating funds of
+    /// the treasury and the yield to the prize pool. Can only be executed by the admin.
     RedeemStable {
         //amount in uusd to be redeemed from Anchor
         amount: Uint128,
     },
-    /// Used to move funds out of the contract. Can only be executed by the admin.
-    Transfer {
-        amount: Uint128
+    /// Withdraws the revenue and contest winnings owed to the sender
+    Claim {},
+    /// Updates the weights of the revenue payees. Can only be executed by the admin.
+    UpdateRevenueSplit {
+        revenue_split: RevenueSplit,
+    },
+    /// Creates a contest. Can only be executed by the admin.
+    CreateContest {
+        contest: ContestMsg,
     },
-    /// Add athlete token information
+    /// Enters a lineup of owned athlete tokens in a contest with the native coin entry fee.
+    /// The tokens are locked until the contest ends and use up one of their usages
+    EnterContest {
+        contest_id: u64,
+        token_ids: Vec<String>,
+    },
+    /// Ranks the entries of an ended contest by the final scores of its game week
+    /// and adds the prizes to the claimable balances of the winners. Every lineup athlete needs
+    /// a final score, a zero score for athletes who did not play. Can only be executed by the admin.
+    SettleContest {
+        contest_id: u64,
+    },
+    /// Records the fantasy points of athletes for game weeks. Can only be executed by a scorer.
+    SubmitScores {
+        scores: Vec<ScoreMsg>,
+    },
+    /// Replaces the points of a score during its dispute period. Can only be executed by the admin.
+    CorrectScore {
+        athlete_id: String,
+        game_week: u64,
+        points: Decimal,
+    },
+    /// Finalizes the scores of athletes for a game week once their dispute period ended.
+    /// Can be called by anyone
+    FinalizeScores {
+        athlete_ids: Vec<String>,
+        game_week: u64,
+    },
+    /// Replaces the stat oracle addresses. Can only be executed by the admin.
+    UpdateScorers {
+        scorers: Vec<String>,
+    },
+    /// Updates the seconds during which a submitted score can be corrected. Can only be executed by the admin.
+    UpdateDisputePeriod {
+        dispute_period: u64,
+    },
+    /// Add athlete token information. Can only be executed by the admin.
     AddAthlete {
-        symbols: Vec<String>,
+        athletes: Vec<AthleteMsg>,
+    },
+    /// Replaces the metadata of an athlete. Can only be executed by the admin.
+    UpdateAthlete {
+        athlete_id: String,
+        metadata: AthleteMetadata,
+    },
+    /// Excludes an athlete from pack and random upgrade draws. Can only be executed by the admin.
+    RetireAthlete {
+        athlete_id: String,
+    },
+    /// Includes a retired athlete in draws again. Can only be executed by the admin.
+    ReactivateAthlete {
+        athlete_id: String,
+    },
+    /// Replaces the supply caps of an athlete. Can only be executed by the admin.
+    UpdateAthleteCaps {
+        athlete_id: String,
+        caps: AthleteCaps,
     },
     /// Performs the turnover of tokens to another instance of Fantasy contract
     TokenTurnover {
@@ -88,35 +313,113 @@ pub enum ExecuteMsg {
     UpdateMarketplace {
         new_contract: String
     },
-    /// Locks an NFT token to be played for Fantasy Sports, can only be called by the NFT owner
-    LockToken {
-        /// Unique ID of the NFT
-        token_id: String,
-        /// Time before a token can be unlocked
-        duration: String,
+    /// Switches the yield source, moving the deposits from the old source to the new one. 
+    /// Can only be executed by the admin.
+    UpdateYieldSource {
+        yield_source: YieldSource,
+    },
+    /// Updates the share of pack payments deposited to the yield source. Can only be executed by the admin.
+    UpdateDepositShare {
+        deposit_share: Decimal,
+    },
+    /// Updates the seconds after a purchase before an unopened pack can be refunded, applies to
+    /// packs bought afterwards. Can only be executed by the admin.
+    UpdateRevealTimeout {
+        reveal_timeout: u64,
+    },
+    /// Replaces the CW20 token contracts accepted as pack payment. Can only be executed by the admin.
+    UpdateAcceptedTokens {
+        tokens: Vec<String>,
+    },
+    /// Adds a new pack type. Can only be executed by the admin.
+    AddPackType {
+        pack_type: PackType,
+    },
+    /// Replaces the settings of an existing pack type, packs already bought keep the slots, odds
+    /// and athletes they were bought with. Can only be executed by the admin.
+    UpdatePackType {
+        pack_type: PackType,
+    },
+    /// Replaces the sale phase schedule. Can only be executed by the admin.
+    UpdateSalePhases {
+        phases: Vec<SalePhase>,
     },
-    /// Checks and unlocks an NFT token if it can be unlocked, can only be called by the NFT owner 
+    /// Updates token usages per rarity, lock durations and upgrade fee. Can only be executed by the admin.
+    UpdateGameRules {
+        game_rules: GameRules,
+    },
+    /// Receives athlete tokens sent with a `NftReceiveMsg` payload
+    ReceiveNft(Cw721ReceiveMsg),
+    /// Returns a locked token to its owner once its unlock date has passed, 
+    /// can only be called by the owner that locked it
     UnlockToken {
         /// Unique ID of the NFT
         token_id: String,
     },
-    /// Exchanges an Athlete token with the same rarity for a higher rarity token
-    UpgradeSameToken {
-        /// Describes the rarity of the NFT 
-        rarity: String,
-        /// NFTs to burn
+    /// Exchanges the input tokens of a recipe for a higher rarity token, paying the recipe fee,
+    /// or the upgrade fee of the game rules if it has none. The output athlete is the one of 
+    /// the inputs for same athlete recipes, which is minted right away. Otherwise the inputs
+    /// are burned and a pending upgrade is bound to a future terrand round, see `RevealUpgrade`.
+    /// Its fee is held until the upgrade is revealed or refunded
+    UpgradeToken {
+        /// Name of the recipe
+        recipe: String,
+        /// NFTs to burn, the contract has to be approved to burn them
         tokens: Vec<String>,
-        /// Athlete ID of the NFTs to be burned/minted
-        athlete_id: String,
     },
-    /// Exchanges any Athlete tokens of the same rarity for a random higher rarity token
-    UpgradeRandToken {
-        /// Describes the rarity of the NFT 
-        rarity: String,
-        /// NFTs to burn
-        tokens: Vec<String>,
-        /// Seed to be used for minting a random new token
-        rand_seed: String
+    /// Draws a random mintable athlete for a pending upgrade once its terrand round is published
+    /// and mints the upgraded token. Can be called by anyone
+    RevealUpgrade {
+        upgrade_id: u64,
+    },
+    /// Mints the burned inputs of a pending upgrade back and returns its fee if the drawn
+    /// output can no longer be minted, e.g. once every athlete of its rarity is capped.
+    /// Can only be called by the upgrade owner
+    RefundUpgrade {
+        upgrade_id: u64,
+    },
+    /// Adds an upgrade recipe. Can only be executed by the admin.
+    AddRecipe {
+        recipe: Recipe,
+    },
+    /// Replaces an existing upgrade recipe. Can only be executed by the admin.
+    UpdateRecipe {
+        recipe: Recipe,
+    },
+    /// Removes an upgrade recipe. Can only be executed by the admin.
+    RemoveRecipe {
+        name: String,
+    },
+}
+
+/// Payloads of athlete tokens sent to the contract
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum NftReceiveMsg {
+    /// Locks the sent token to be played for Fantasy Sports, using up one of its usages.
+    /// The contract holds the token until it is unlocked
+    LockToken {
+        /// Name of a lock duration from the game rules, e.g. "day"
+        duration: String,
+    },
+}
+
+/// Payloads of CW20 tokens sent to the contract
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveMsg {
+    /// Purchase an athlete token pack of the given type with the sent CW20 tokens
+    PurchasePack {
+        pack_type: String,
+        /// Hex encoded Merkle proof of the sender address, required during allowlist phases
+        proof: Option<Vec<String>>,
+        /// Address that referred the buyer
+        referrer: Option<String>,
+    },
+    /// Enters a lineup in a contest paying the entry fee with the sent CW20 tokens
+    EnterContest {
+        contest_id: u64,
+        token_ids: Vec<String>,
     },
 }
 
@@ -125,20 +428,73 @@ pub enum ExecuteMsg {
 pub enum QueryMsg {
     /// Returns the contract info for the Playible Contract
     ContractInfo {},
-    /// Returns the price for purchasing a pack
-    PackPrice {},
-    /// Returns the total deposited stable coin amount to Anchor
+    /// Returns the pack types on sale at the current block time
+    PackTypes {},
+    /// Returns the settings and sales of a pack type
+    PackType {
+        name: String,
+    },
+    /// Returns the sale phase schedule and the phase running at the current block time
+    SalePhases {},
+    /// Returns the allocation of an address used during a sale phase
+    RemainingAllocation {
+        phase: String,
+        address: String,
+    },
+    /// Returns the token usages per rarity, lock durations and upgrade fee
+    GameRules {},
+    /// Returns the weights of the revenue payees
+    RevenueSplit {},
+    /// Returns the revenue and contest winnings owed to an address per denom
+    Claimable {
+        address: String,
+    },
+    /// Returns the total deposited stable coin amount to the yield source
     TotalDeposit {},
+    /// Returns the principal deposited to the yield source, the operating funds claimable by the
+    /// treasury and the prize pool kept in the contract
+    Funds {},
+    /// Returns the principal, shares and accrued yield of the yield source deposits
+    YieldInfo {},
     /// Returns the token information of the corresponding Athlete id
     AthleteInfo {
         athlete_id: String,
     },
     /// Returns the total number of unique Athlete tokens saved 
     AthleteCount {},
+    /// Returns the token information of the Athletes, ordered by Athlete id
+    ListAthletes {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the token information of the Athlete using the given symbol
+    AthleteBySymbol {
+        symbol: String,
+    },
     /// Returns a boolean if the token is mintable
     IsTokenMintable {
         athlete_id: String,
-        rarity: String,
+        rarity: Rarity,
+    },
+    /// Returns the cap, minted and remaining supply per rarity of an athlete
+    AthleteSupply {
+        athlete_id: String,
+    },
+    /// Decodes a token ID into its athlete, rarity and serial
+    DecodeTokenId {
+        token_id: String,
+    },
+    /// Returns the upgrade recipes ordered by name
+    Recipes {},
+    /// Returns an upgrade recipe
+    Recipe {
+        name: String,
+    },
+    /// Returns the tokens an owner has locked, ordered by token id
+    LockedTokens {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
     },
     /// Checks if a locked NFT can be unlocked
     CanUnlockToken {
@@ -150,6 +506,68 @@ pub enum QueryMsg {
         /// Token ID of the NFT to be queried
         token_id: String,
     },
+    /// Returns the buyer, terrand round and minted tokens of a purchased pack
+    PackInfo {
+        pack_id: u64,
+    },
+    /// Returns the packs of a buyer that are still waiting to be opened
+    PendingPacks {
+        buyer: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns the owner, terrand round and minted token of a random output upgrade
+    Upgrade {
+        upgrade_id: u64,
+    },
+    /// Returns the upgrades of an owner that are still waiting to be revealed
+    PendingUpgrades {
+        owner: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns the score of an athlete for a game week
+    AthleteScore {
+        athlete_id: String,
+        game_week: u64,
+    },
+    /// Returns the scores of an athlete ordered by game week
+    AthleteScores {
+        athlete_id: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns the settings, prize pool and entry count of a contest
+    Contest {
+        contest_id: u64,
+    },
+    /// Returns the lineup rules of a contest
+    ContestRules {
+        contest_id: u64,
+    },
+    /// Returns the contests ordered by contest id
+    Contests {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns the lineups entered in a contest ordered by entry id
+    ContestEntries {
+        contest_id: u64,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns the ranked entries of a settled contest ordered by rank
+    Leaderboard {
+        contest_id: u64,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns the lineups entered by an address in all contests ordered by entry id
+    Lineups {
+        owner: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
 }
 
 /// CW721 Contract Messages
@@ -179,21 +597,48 @@ pub enum TokenMsg {
         minter: String,
     },
     TransferNft { 
-        /// Burn Address (Fantasy Contract Address)
+        /// Address receiving the NFT
         recipient: String, 
-        /// Token ID of the NFT to be transferred/burned
+        /// Token ID of the NFT to be transferred
         token_id: String 
     },
+    Burn {
+        /// Token ID of the NFT to be burned
+        token_id: String,
+    },
     NftInfo {
         /// Token ID of the NFT to be queried
         token_id: String,
     },
+    OwnerOf {
+        /// Token ID of the NFT to be queried
+        token_id: String,
+        /// Whether to include expired approvals
+        include_expired: Option<bool>,
+    },
+    AllNftInfo {
+        /// Token ID of the NFT to be queried
+        token_id: String,
+        /// Whether to include expired approvals
+        include_expired: Option<bool>,
+    },
+    ApprovedForAll {
+        /// Address whose operators are queried
+        owner: String,
+        /// Whether to include expired approvals
+        include_expired: Option<bool>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
 }
 
 /// Terrand Messages
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum TerrandMsg {
+    GetRandomness {
+        round: u64
+    },
     LatestDrand {}
 }
 
@@ -205,40 +650,8 @@ pub struct LatestRandomResponse {
     pub worker: String,
 }
 
-/// Anchor Messages
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
-#[serde(rename_all = "snake_case")]
-pub enum AnchorMsg {
-    Config {},
-    DepositStable {},
-    Receive(Cw20ReceiveMsg),
-    RedeemStable {},
-}
-
-/// Anchor Responses
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct StateResponse {
-    pub total_liabilities: Decimal256,
-    pub total_reserves: Decimal256,
-    pub last_interest_updated: u64,
-    pub last_reward_updated: u64,
-    pub global_interest_index: Decimal256,
-    pub global_reward_index: Decimal256,
-    pub anc_emission_rate: Decimal256,
-    pub prev_aterra_supply: Uint256,
-    pub prev_exchange_rate: Decimal256,
+pub struct GetRandomResponse {
+    pub randomness: Binary,
+    pub worker: String,
 }
-
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct ConfigResponse {
-    pub contract_addr: CanonicalAddr,
-    pub owner_addr: CanonicalAddr,
-    pub aterra_contract: CanonicalAddr,
-    pub interest_model: CanonicalAddr,
-    pub distribution_model: CanonicalAddr,
-    pub overseer_contract: CanonicalAddr,
-    pub collector_contract: CanonicalAddr,
-    pub distributor_contract: CanonicalAddr,
-    pub stable_denom: String,
-    pub max_borrow_factor: Decimal256,
-}
\ No newline at end of file
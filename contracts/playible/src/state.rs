@@ -1,16 +1,22 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, StdResult, Storage};
+use std::fmt;
+
+use cosmwasm_std::{Addr, Binary, Coin, Decimal, StdResult, Storage, Timestamp, Uint128};
 use cosmwasm_storage::{bucket, bucket_read, Bucket, ReadonlyBucket};
-use cw_storage_plus::{Item};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex, U64Key};
+use cw20::Denom;
+use cw721_base::Rarity;
+
+use crate::yield_source::YieldSource;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ContractInfoResponse {
     /// Stable coin denomination. 
     pub stable_denom: String,
-    // anchor contract address for depositing the rewards
-    pub anchor_addr: Addr,
+    /// where the deposited share of the pack proceeds earns yield
+    pub yield_source: YieldSource,
     // terrand contract address for calling Oracle's DRand
     pub terrand_addr: Addr,
     /// contract admin
@@ -19,50 +25,710 @@ pub struct ContractInfoResponse {
     pub athlete_addr: Addr,
     /// contract address for the Marketplace contract
     pub marketplace_addr: Addr,
-    /// number of NFT players to be pulled per pack
+    /// CW20 token contracts that can be used as pack payment
+    pub accepted_tokens: Vec<Addr>,
+    /// seconds after a purchase before an unopened pack can be refunded
+    pub reveal_timeout: u64,
+    /// share of each opened pack's stable coin payment deposited to the yield source
+    pub deposit_share: Decimal,
+    /// stat oracle addresses allowed to submit athlete scores
+    pub scorers: Vec<Addr>,
+    /// seconds after submission during which the admin can correct a score
+    pub dispute_period: u64,
+    /// Maximum number of tokens ever minted per athlete for each rarity, burning does not free up supply
+    pub caps: PerRarity<u64>,
+}
+
+/// One value for each rarity, e.g. the supply caps or the token usages
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema, Debug, Default)]
+pub struct PerRarity<T> {
+    pub common: T,
+    pub uncommon: T,
+    pub rare: T,
+    pub legendary: T,
+}
+
+impl<T> PerRarity<T> {
+    pub fn get(&self, rarity: Rarity) -> &T {
+        match rarity {
+            Rarity::Common => &self.common,
+            Rarity::Uncommon => &self.uncommon,
+            Rarity::Rare => &self.rare,
+            Rarity::Legendary => &self.legendary,
+        }
+    }
+
+    pub fn get_mut(&mut self, rarity: Rarity) -> &mut T {
+        match rarity {
+            Rarity::Common => &mut self.common,
+            Rarity::Uncommon => &mut self.uncommon,
+            Rarity::Rare => &mut self.rare,
+            Rarity::Legendary => &mut self.legendary,
+        }
+    }
+}
+
+/// Letter identifying each rarity in token IDs
+pub const RARITY_SUFFIXES: PerRarity<&str> = PerRarity {
+    common: "C",
+    uncommon: "U",
+    rare: "R",
+    legendary: "L",
+};
+
+pub fn rarity_suffix(rarity: Rarity) -> &'static str {
+    RARITY_SUFFIXES.get(rarity)
+}
+
+pub fn rarity_from_suffix(suffix: &str) -> Option<Rarity> {
+    Rarity::ALL.iter().copied().find(|&rarity| rarity_suffix(rarity) == suffix)
+}
+
+/// Separates the athlete symbol, rarity suffix and serial of a token ID, e.g. "LBJ-C-12".
+/// Athlete symbols cannot contain it so that IDs can be decoded unambiguously.
+pub const TOKEN_ID_SEPARATOR: char = '-';
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct LockDuration {
+    /// Name used when locking a token, e.g. "day"
+    pub name: String,
+    /// Number of seconds the token stays locked
+    pub seconds: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct GameRules {
+    /// Number of times a newly minted token can be locked up for a game, per rarity
+    pub usage: PerRarity<u64>,
+    /// Durations a token can be locked up for
+    pub lock_durations: Vec<LockDuration>,
+    /// Fee paid for upgrades whose recipe has no fee, upgrades are free if unset
+    #[serde(default)]
+    pub upgrade_fee: Option<Coin>,
+    /// Multipliers applied to the fantasy points of a token in contests, per rarity
+    #[serde(default = "default_multipliers")]
+    pub multipliers: PerRarity<Decimal>,
+}
+
+fn default_multipliers() -> PerRarity<Decimal> {
+    PerRarity {
+        common: Decimal::one(),
+        uncommon: Decimal::one(),
+        rare: Decimal::one(),
+        legendary: Decimal::one(),
+    }
+}
+
+impl GameRules {
+    /// Returns the number of seconds of a named lock duration
+    pub fn lock_seconds(&self, name: &str) -> Option<u64> {
+        self.lock_durations
+            .iter()
+            .find(|d| d.name == name)
+            .map(|d| d.seconds)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Payee {
+    pub address: Addr,
+    /// Relative weight of the payee in the revenue split
+    pub weight: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RevenueSplit {
+    /// Treasury payee, which also receives the rounding leftovers and the 
+    /// referrer share of purchases without a referrer
+    pub treasury: Payee,
+    /// Weight of the prize pool kept in the contract for contests
+    pub prize_pool_weight: u64,
+    /// Athlete and partner royalty payees
+    pub royalties: Vec<Payee>,
+    /// Weight paid to the address that referred the buyer
+    pub referrer_weight: u64,
+}
+
+impl RevenueSplit {
+    pub fn total_weight(&self) -> u64 {
+        self.treasury.weight +
+            self.prize_pool_weight +
+            self.royalties.iter().map(|p| p.weight).sum::<u64>() +
+            self.referrer_weight
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RarityOdds {
+    /// Rarity of the pack slot
+    pub rarity: Rarity,
+    /// Relative weight of the rarity, e.g. 80 for Common and 1 for Legendary
+    pub weight: u64,
+}
+
+/// Amount of a native coin or of a CW20 token
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Price {
+    pub denom: Denom,
+    pub amount: Uint128,
+}
+
+impl Price {
+    /// Returns the native denom or the CW20 contract address
+    pub fn denom_name(&self) -> String {
+        match &self.denom {
+            Denom::Native(denom) => denom.clone(),
+            Denom::Cw20(contract_addr) => contract_addr.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Price {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.amount, self.denom_name())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PackType {
+    /// Unique name of the pack type, e.g. "starter"
+    pub name: String,
+    /// Payment options of each pack, in native coins or CW20 tokens
+    pub prices: Vec<Price>,
+    /// Number of Player NFTs to be pulled per pack
     pub pack_len: u64,
-    /// price of each pack
-    pub pack_price: u64,
-    // Maximum number tokens to be minted for each rarity
-    pub common_cap: u64,
-    pub uncommon_cap: u64,
-    pub rare_cap: u64,
-    pub legendary_cap: u64,
+    /// Rarity weights used for drawing each slot of a pack
+    pub odds: Vec<RarityOdds>,
+    /// Maximum number of packs that can be sold, unlimited if unset
+    pub supply: Option<u64>,
+    /// Maximum number of packs a single address can buy, unlimited if unset
+    pub wallet_limit: Option<u64>,
+    /// Time at which the sale starts, immediately if unset
+    pub start_time: Option<Timestamp>,
+    /// Time at which the sale ends, never if unset
+    pub end_time: Option<Timestamp>,
+    /// Athlete IDs that can be drawn from the pack, every athlete if unset
+    pub athlete_ids: Option<Vec<String>>,
+}
+
+impl PackType {
+    /// Returns true if the pack type is being sold at the given time
+    pub fn is_on_sale(&self, time: Timestamp) -> bool {
+        self.start_time.map_or(true, |start| time >= start) &&
+            self.end_time.map_or(true, |end| time < end)
+    }
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SalePhase {
+    /// Unique name of the phase, e.g. "presale"
+    pub name: String,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+    /// Hex encoded sha256 Merkle root of the allowed addresses, open to everyone if unset
+    pub merkle_root: Option<String>,
+    /// Maximum number of packs each address can buy during the phase, unlimited if unset
+    pub allocation: Option<u64>,
+}
+
+impl SalePhase {
+    /// Returns true if the phase is running at the given time
+    pub fn is_active(&self, time: Timestamp) -> bool {
+        time >= self.start_time && time < self.end_time
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct AthleteMetadata {
+    /// Name of the athlete shown in the app
+    pub display_name: Option<String>,
+    pub sport: Option<String>,
+    pub team: Option<String>,
+    pub position: Option<String>,
+    pub season: Option<String>,
+    /// Base URI of the athlete images
+    pub image_uri: Option<String>,
+}
+
+/// Per-athlete supply caps, unset rarities fall back to the global caps
+pub type AthleteCaps = PerRarity<Option<u64>>;
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct AthleteInfo {
     /// Symbol used for token_id generation
     pub symbol: String,
     /// Current number of minted tokens per rarity
-    pub common_count: u64,
-    pub uncommon_count: u64,
-    pub rare_count: u64,
-    pub legendary_count: u64,
+    pub minted: PerRarity<u64>,
+    /// Descriptive information about the athlete
+    #[serde(default)]
+    pub metadata: AthleteMetadata,
+    /// Retired athletes are no longer drawn from packs or random upgrades,
+    /// their minted tokens stay valid
+    #[serde(default)]
+    pub is_retired: bool,
+    /// Supply caps overriding the global caps
+    #[serde(default)]
+    pub caps: AthleteCaps,
+    /// Number of tokens burned by upgrades per rarity, only reported since the caps
+    /// limit the minted tokens and burns sent straight to the token contract are not seen
+    #[serde(default)]
+    pub burned: PerRarity<u64>,
+}
+
+impl AthleteInfo {
+    /// Returns the supply cap for a rarity, defaulting to the global cap
+    pub fn cap(&self, contract_info: &ContractInfoResponse, rarity: Rarity) -> u64 {
+        self.caps.get(rarity).unwrap_or(*contract_info.caps.get(rarity))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum PackStatus {
+    /// Waiting for its terrand round to be published
+    Pending,
+    /// Athletes have been revealed and minted to the buyer
+    Opened,
+    /// Payment has been returned to the buyer after the reveal timeout, or because
+    /// its athletes could no longer be minted
+    Refunded,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PackInfo {
+    /// Unique ID of the pack
+    pub pack_id: u64,
+    /// Name of the purchased pack type
+    pub pack_type: String,
+    /// Address that purchased the pack
+    pub buyer: Addr,
+    /// Sale phase running at purchase time
+    pub sale_phase: Option<String>,
+    /// Address that referred the buyer, paid its share of the revenue when the pack is opened
+    pub referrer: Option<Addr>,
+    /// Future terrand round used for drawing the athletes of the pack, anyone can relay it
+    /// to terrand from the drand history
+    pub round: u64,
+    /// Payment held in escrow until the pack is opened or refunded
+    pub price: Price,
+    /// Number of slots, odds and eligible athletes of the pack type at purchase time,
+    /// so later pack type updates do not change packs that are already paid for
+    pub pack_len: u64,
+    pub odds: Vec<RarityOdds>,
+    pub athlete_ids: Option<Vec<String>>,
+    /// Time after which the pack can be refunded if terrand has not reached its round
+    pub expires_at: Timestamp,
+    pub status: PackStatus,
+    /// Randomness of the terrand round, kept so the draw can be audited
+    pub randomness: Option<Binary>,
+    /// Token IDs minted from the pack
+    pub token_ids: Vec<String>,
+}
+
+impl PackInfo {
+    /// Returns true if the athlete can be drawn from the pack
+    pub fn is_eligible(&self, athlete_id: &str) -> bool {
+        self.athlete_ids
+            .as_ref()
+            .map_or(true, |ids| ids.iter().any(|id| id == athlete_id))
+    }
+}
+
+pub struct PackIndexes<'a> {
+    // pk goes to second tuple element
+    pub buyer: MultiIndex<'a, (Addr, Vec<u8>), PackInfo>,
+}
+
+impl<'a> IndexList<PackInfo> for PackIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<PackInfo>> + '_> {
+        let v: Vec<&dyn Index<PackInfo>> = vec![&self.buyer];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn pack_buyer_idx(d: &PackInfo, k: Vec<u8>) -> (Addr, Vec<u8>) {
+    (d.buyer.clone(), k)
+}
+
+pub fn packs<'a>() -> IndexedMap<'a, U64Key, PackInfo, PackIndexes<'a>> {
+    let indexes = PackIndexes {
+        buyer: MultiIndex::new(pack_buyer_idx, "packs", "packs__buyer"),
+    };
+    IndexedMap::new("packs", indexes)
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreStatus {
+    /// Submitted by a scorer, can be corrected until the dispute period ends
+    Submitted,
+    /// Corrected by the admin during the dispute period
+    Corrected,
+    /// Final and usable for contest settlement
+    Finalized,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AthleteScore {
+    pub athlete_id: String,
+    pub game_week: u64,
+    /// Fantasy points scored by the athlete during the game week
+    pub points: Decimal,
+    /// Scorer that submitted the record
+    pub scorer: Addr,
+    /// Time after which the score can no longer be corrected and can be finalized
+    pub dispute_ends: Timestamp,
+    pub status: ScoreStatus,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PositionQuota {
+    /// Position from the athlete metadata, e.g. "guard"
+    pub position: String,
+    /// Minimum number of tokens playing the position
+    pub min: u32,
+    /// Maximum number of tokens playing the position
+    pub max: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SalaryCap {
+    pub budget: u64,
+    /// Points each rarity costs against the budget of a lineup
+    pub costs: PerRarity<u64>,
+}
+
+/// Constraints a lineup has to meet to enter a contest, unset rules are not enforced
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct LineupRules {
+    /// Maximum number of tokens of the same athlete
+    pub max_per_athlete: Option<u32>,
+    /// Number of tokens allowed per position, positions without a quota are unrestricted
+    pub position_quotas: Vec<PositionQuota>,
+    pub salary_cap: Option<SalaryCap>,
+    /// Athletes playing in the slate of games of the contest
+    pub eligible_athletes: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Contest {
+    /// Unique ID of the contest
+    pub contest_id: u64,
+    pub name: String,
+    /// Lineups can be entered from this time
+    pub entry_start: Timestamp,
+    /// Lineups can be entered until this time
+    pub entry_end: Timestamp,
+    /// Time the contest ends, entered tokens stay locked until then
+    pub end_time: Timestamp,
+    /// Game week of the athlete scores the lineups are ranked by
+    pub game_week: u64,
+    /// Number of tokens in a lineup
+    pub lineup_size: u32,
+    #[serde(default)]
+    pub rules: LineupRules,
+    /// Fee paid for each entry, added to the prize pool of the contest
+    pub entry_fee: Option<Price>,
+    /// Prizes of the contest, seeded from the contract prize pool and grown by the entry fees
+    pub prize_pool: Option<Price>,
+    /// Share of the prize pool paid to each rank, starting from the first.
+    /// Unpaid shares go back to the contract prize pool
+    pub payouts: Vec<Decimal>,
+    /// Maximum number of lineups that can be entered
+    pub max_entries: u64,
+    /// Number of lineups entered
+    pub entries: u64,
+    /// Set once the entries have been ranked and the prizes paid
+    pub settled: bool,
+}
+
+impl Contest {
+    /// Returns true if lineups can be entered at the given time
+    pub fn is_open(&self, time: Timestamp) -> bool {
+        self.entry_start <= time && time < self.entry_end
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ContestEntry {
+    /// Unique ID of the entry
+    pub entry_id: u64,
+    pub contest_id: u64,
+    /// Address that entered the lineup
+    pub owner: Addr,
+    /// Tokens of the lineup, locked until the contest ends
+    pub lineup: Vec<LineupSlot>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct LineupSlot {
+    pub token_id: String,
+    /// Athlete and rarity of the token at entry time, used to score the lineup
+    pub athlete_id: String,
+    pub rarity: Rarity,
+}
+
+/// Ranking of a contest entry once the contest is settled
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Standing {
+    /// Position in the leaderboard starting at 1, ties go to the earliest entry
+    pub rank: u64,
+    pub entry_id: u64,
+    pub owner: Addr,
+    /// Sum of the fantasy points of the lineup tokens times their rarity multiplier
+    pub score: Decimal,
+    /// Winnings added to the claimable balance of the owner
+    pub prize: Option<Price>,
+}
+
+pub struct EntryIndexes<'a> {
+    // pk goes to second tuple element
+    pub contest: MultiIndex<'a, (U64Key, Vec<u8>), ContestEntry>,
+    pub owner: MultiIndex<'a, (Addr, Vec<u8>), ContestEntry>,
+}
+
+impl<'a> IndexList<ContestEntry> for EntryIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<ContestEntry>> + '_> {
+        let v: Vec<&dyn Index<ContestEntry>> = vec![&self.contest, &self.owner];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn entry_contest_idx(d: &ContestEntry, k: Vec<u8>) -> (U64Key, Vec<u8>) {
+    (U64Key::from(d.contest_id), k)
+}
+
+pub fn entry_owner_idx(d: &ContestEntry, k: Vec<u8>) -> (Addr, Vec<u8>) {
+    (d.owner.clone(), k)
+}
+
+pub fn contest_entries<'a>() -> IndexedMap<'a, U64Key, ContestEntry, EntryIndexes<'a>> {
+    let indexes = EntryIndexes {
+        contest: MultiIndex::new(entry_contest_idx, "contest_entries", "contest_entries__contest"),
+        owner: MultiIndex::new(entry_owner_idx, "contest_entries", "contest_entries__owner"),
+    };
+    IndexedMap::new("contest_entries", indexes)
+}
+
+/// Upgrade exchanging tokens of a rarity for a token of a higher rarity
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Recipe {
+    /// Unique name of the recipe
+    pub name: String,
+    /// Rarity of the input tokens
+    pub input_rarity: Rarity,
+    /// Number of input tokens burned
+    pub input_count: u32,
+    /// Whether the inputs have to be of the same athlete, who is then also the output athlete
+    pub same_athlete: bool,
+    /// Rarity of the minted token
+    pub output_rarity: Rarity,
+    /// Fee paid for the upgrade, defaults to the upgrade fee of the game rules
+    pub fee: Option<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum UpgradeStatus {
+    /// Waiting for its terrand round to be published
+    Pending,
+    /// Output athlete has been drawn and minted to the owner
+    Revealed,
+    /// Inputs have been minted back and the fee returned since the output could not be minted
+    Refunded,
+}
+
+/// Input token burned by a random output upgrade, minted back if the upgrade is refunded
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct UpgradeInput {
+    pub token_id: String,
+    pub athlete_id: String,
+    pub rarity: Rarity,
+    /// Usages the token had left
+    pub usage: u64,
+}
+
+/// Upgrade of a random output recipe waiting for its terrand round to draw the output athlete
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PendingUpgrade {
+    /// Unique ID of the upgrade
+    pub upgrade_id: u64,
+    /// Address the upgraded token is minted to
+    pub owner: Addr,
+    /// Name of the recipe used
+    pub recipe: String,
+    /// Rarity of the token to mint
+    pub rarity: Rarity,
+    /// Burned input tokens
+    pub inputs: Vec<UpgradeInput>,
+    /// Fee held in escrow until the upgrade is revealed or refunded
+    pub fee: Option<Coin>,
+    /// Future terrand round used for drawing the athlete, anyone can relay it to terrand
+    /// from the drand history
+    pub round: u64,
+    pub status: UpgradeStatus,
+    /// Randomness of the terrand round, kept so the draw can be audited
+    pub randomness: Option<Binary>,
+    /// Token ID minted once the upgrade is revealed
+    pub token_id: Option<String>,
+}
+
+pub struct UpgradeIndexes<'a> {
+    // pk goes to second tuple element
+    pub owner: MultiIndex<'a, (Addr, Vec<u8>), PendingUpgrade>,
+}
+
+impl<'a> IndexList<PendingUpgrade> for UpgradeIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<PendingUpgrade>> + '_> {
+        let v: Vec<&dyn Index<PendingUpgrade>> = vec![&self.owner];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn upgrade_owner_idx(d: &PendingUpgrade, k: Vec<u8>) -> (Addr, Vec<u8>) {
+    (d.owner.clone(), k)
+}
+
+pub fn upgrades<'a>() -> IndexedMap<'a, U64Key, PendingUpgrade, UpgradeIndexes<'a>> {
+    let indexes = UpgradeIndexes {
+        owner: MultiIndex::new(upgrade_owner_idx, "upgrades", "upgrades__owner"),
+    };
+    IndexedMap::new("upgrades", indexes)
+}
+
+/// Token held by the contract while it is locked for a game
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct LockedToken {
+    pub token_id: String,
+    /// Address that sent the token, the only one it can be returned to
+    pub owner: Addr,
+    /// Name of the lock duration from the game rules
+    pub duration: String,
+    /// Time after which the owner can unlock the token
+    pub unlock_date: Timestamp,
+}
+
+pub struct LockedTokenIndexes<'a> {
+    // pk goes to second tuple element
+    pub owner: MultiIndex<'a, (Addr, Vec<u8>), LockedToken>,
+}
+
+impl<'a> IndexList<LockedToken> for LockedTokenIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<LockedToken>> + '_> {
+        let v: Vec<&dyn Index<LockedToken>> = vec![&self.owner];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn locked_token_owner_idx(d: &LockedToken, k: Vec<u8>) -> (Addr, Vec<u8>) {
+    (d.owner.clone(), k)
+}
+
+pub fn locked_tokens<'a>() -> IndexedMap<'a, &'a str, LockedToken, LockedTokenIndexes<'a>> {
+    let indexes = LockedTokenIndexes {
+        owner: MultiIndex::new(locked_token_owner_idx, "locked_tokens", "locked_tokens__owner"),
+    };
+    IndexedMap::new("locked_tokens", indexes)
 }
 
 pub const CONTRACT_INFO: Item<ContractInfoResponse> = Item::new("contract_info");
-pub const TOTAL_DEPOSIT: Item<u64> = Item::new("total_deposit");
-pub const LAST_ROUND: Item<u64>  = Item::new("last_round");
+pub const GAME_RULES: Item<GameRules> = Item::new("game_rules");
+/// Stable coin principal deposited to the yield source
+pub const TOTAL_DEPOSIT: Item<Uint128> = Item::new("total_deposit");
+/// Shares held in the yield source in exchange for its deposits, e.g. aUST for Anchor
+pub const YIELD_SHARES: Item<Uint128> = Item::new("yield_shares");
+pub const REVENUE_SPLIT: Item<RevenueSplit> = Item::new("revenue_split");
+/// Funds kept in the contract for contest prizes, indexed by denom name
+pub const PRIZE_POOL: Map<&str, Price> = Map::new("prize_pool");
+/// Revenue owed to each payee, indexed by payee address and denom name
+pub const CLAIMABLE: Map<(&Addr, &str), Price> = Map::new("claimable");
 pub const ATHLETE_LIST_PREFIX: &[u8] = b"athlete_list";
 pub const ATHLETE_COUNT: Item<u64>  = Item::new("athlete_count");
+/// Athlete IDs indexed by their symbol
+pub const ATHLETE_SYMBOLS: Map<&str, u64> = Map::new("athlete_symbols");
+pub const PACK_COUNT: Item<u64> = Item::new("pack_count");
+/// Pack types indexed by their name
+pub const PACK_TYPES: Map<&str, PackType> = Map::new("pack_types");
+/// Number of packs sold per pack type, refunded packs excluded
+pub const PACK_TYPE_SALES: Map<&str, u64> = Map::new("pack_type_sales");
+/// Number of packs sold per pack type and buyer, refunded packs excluded
+pub const WALLET_PACK_SALES: Map<(&str, &Addr), u64> = Map::new("wallet_pack_sales");
+/// Sale phases ordered by start time
+pub const SALE_PHASES: Item<Vec<SalePhase>> = Item::new("sale_phases");
+/// Number of packs bought per sale phase and buyer, refunded packs excluded
+pub const PHASE_PURCHASES: Map<(&str, &Addr), u64> = Map::new("phase_purchases");
+pub const CONTESTS: Map<U64Key, Contest> = Map::new("contests");
+pub const RECIPES: Map<&str, Recipe> = Map::new("recipes");
+pub const SCORES: Map<(&str, U64Key), AthleteScore> = Map::new("scores");
+pub const LEADERBOARDS: Map<(U64Key, U64Key), Standing> = Map::new("leaderboards");
+pub const CONTEST_COUNT: Item<u64> = Item::new("contest_count");
+pub const ENTRY_COUNT: Item<u64> = Item::new("entry_count");
+pub const UPGRADE_COUNT: Item<u64> = Item::new("upgrade_count");
+// Last serial used in token IDs, keyed by athlete ID and rarity suffix
+pub const TOKEN_SERIALS: Map<(&str, &str), u64> = Map::new("token_serials");
 
-pub fn total_deposit(storage: &dyn Storage) -> StdResult<u64> {
+pub fn total_deposit(storage: &dyn Storage) -> StdResult<Uint128> {
     Ok(TOTAL_DEPOSIT.may_load(storage)?.unwrap_or_default())
 }
 
-pub fn increase_deposit(storage: &mut dyn Storage, amount: u64) -> StdResult<u64> {
-    let val = total_deposit(storage)? + amount;
+pub fn increase_deposit(storage: &mut dyn Storage, amount: Uint128) -> StdResult<Uint128> {
+    let val = total_deposit(storage)?.checked_add(amount)?;
     TOTAL_DEPOSIT.save(storage, &val)?;
     Ok(val)
 }
 
-pub fn decrease_deposit(storage: &mut dyn Storage, amount: u64) -> StdResult<u64> {
-    let val = total_deposit(storage)? - amount;
+pub fn decrease_deposit(storage: &mut dyn Storage, amount: Uint128) -> StdResult<Uint128> {
+    let val = total_deposit(storage)?.checked_sub(amount)?;
     TOTAL_DEPOSIT.save(storage, &val)?;
     Ok(val)
 }
 
+pub fn yield_shares(storage: &dyn Storage) -> StdResult<Uint128> {
+    Ok(YIELD_SHARES.may_load(storage)?.unwrap_or_default())
+}
+
+pub fn increase_yield_shares(storage: &mut dyn Storage, amount: Uint128) -> StdResult<Uint128> {
+    let val = yield_shares(storage)?.checked_add(amount)?;
+    YIELD_SHARES.save(storage, &val)?;
+    Ok(val)
+}
+
+pub fn decrease_yield_shares(storage: &mut dyn Storage, amount: Uint128) -> StdResult<Uint128> {
+    let val = yield_shares(storage)?.checked_sub(amount)?;
+    YIELD_SHARES.save(storage, &val)?;
+    Ok(val)
+}
+
+pub fn increase_prize_pool(storage: &mut dyn Storage, funds: &Price) -> StdResult<Price> {
+    PRIZE_POOL.update(storage, &funds.denom_name(), |old| -> StdResult<_> {
+        let amount = old.map(|p| p.amount).unwrap_or_default();
+        Ok(Price {
+            denom: funds.denom.clone(),
+            amount: amount.checked_add(funds.amount)?,
+        })
+    })
+}
+
+pub fn decrease_prize_pool(storage: &mut dyn Storage, funds: &Price) -> StdResult<Price> {
+    PRIZE_POOL.update(storage, &funds.denom_name(), |old| -> StdResult<_> {
+        let amount = old.map(|p| p.amount).unwrap_or_default();
+        Ok(Price {
+            denom: funds.denom.clone(),
+            amount: amount.checked_sub(funds.amount)?,
+        })
+    })
+}
+
+pub fn increase_claimable(storage: &mut dyn Storage, payee: &Addr, funds: &Price) -> StdResult<Price> {
+    CLAIMABLE.update(storage, (payee, &funds.denom_name()), |old| -> StdResult<_> {
+        let amount = old.map(|p| p.amount).unwrap_or_default();
+        Ok(Price {
+            denom: funds.denom.clone(),
+            amount: amount.checked_add(funds.amount)?,
+        })
+    })
+}
+
 pub fn athlete_count(storage: &dyn Storage) -> StdResult<u64> {
     Ok(ATHLETE_COUNT.may_load(storage)?.unwrap_or_default())
 }
@@ -73,6 +739,42 @@ pub fn increment_athlete_count(storage: &mut dyn Storage) -> StdResult<u64> {
     Ok(val)
 }
 
+pub fn pack_count(storage: &dyn Storage) -> StdResult<u64> {
+    Ok(PACK_COUNT.may_load(storage)?.unwrap_or_default())
+}
+
+pub fn increment_pack_count(storage: &mut dyn Storage) -> StdResult<u64> {
+    let val = pack_count(storage)? + 1;
+    PACK_COUNT.save(storage, &val)?;
+    Ok(val)
+}
+
+pub fn increment_contest_count(storage: &mut dyn Storage) -> StdResult<u64> {
+    let val = CONTEST_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+    CONTEST_COUNT.save(storage, &val)?;
+    Ok(val)
+}
+
+pub fn increment_entry_count(storage: &mut dyn Storage) -> StdResult<u64> {
+    let val = ENTRY_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+    ENTRY_COUNT.save(storage, &val)?;
+    Ok(val)
+}
+
+pub fn increment_upgrade_count(storage: &mut dyn Storage) -> StdResult<u64> {
+    let val = UPGRADE_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+    UPGRADE_COUNT.save(storage, &val)?;
+    Ok(val)
+}
+
+pub fn token_serial(storage: &dyn Storage, athlete_id: &str, rarity: Rarity) -> StdResult<u64> {
+    Ok(TOKEN_SERIALS.may_load(storage, (athlete_id, rarity_suffix(rarity)))?.unwrap_or_default())
+}
+
+pub fn save_token_serial(storage: &mut dyn Storage, athlete_id: &str, rarity: Rarity, serial: u64) -> StdResult<()> {
+    TOKEN_SERIALS.save(storage, (athlete_id, rarity_suffix(rarity)), &serial)
+}
+
 pub fn athlete_list(storage: &mut dyn Storage) -> Bucket<AthleteInfo> {
     bucket(storage, ATHLETE_LIST_PREFIX)
 }
@@ -0,0 +1,113 @@
+use cosmwasm_std::{Addr, Api, Coin, CosmosMsg, Decimal, Deps, StdResult, Uint128};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::anchor;
+
+pub const DECIMAL_FRACTION: Uint128 = Uint128::new(1_000_000_000_000_000_000);
+
+/// Where the deposited share of the pack proceeds earns yield
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum YieldSource {
+    /// Deposits stable coins in Anchor's money market in exchange for aUST
+    Anchor { anchor_addr: Addr },
+    /// Keeps the deposits in the contract without earning yield
+    HoldFunds {},
+    /// Keeps the deposits in the contract and values its shares at an admin set
+    /// exchange rate, used for testing prizes without a money market
+    #[cfg(feature = "mock-market")]
+    MockMarket { exchange_rate: Decimal },
+}
+
+/// Outcome of a deposit or redemption with a yield source
+pub enum YieldAction {
+    /// Settled once the message replies with the actual amounts
+    Pending(CosmosMsg),
+    /// Settled right away with the stable coin amount and the shares minted or burned
+    Settled { amount: Uint128, shares: Uint128 },
+}
+
+impl YieldSource {
+    /// Checks the addresses and the exchange rate of the yield source
+    pub fn validate(&self, api: &dyn Api) -> StdResult<()> {
+        match self {
+            YieldSource::Anchor { anchor_addr } => {
+                api.addr_validate(anchor_addr.as_str())?;
+            }
+            YieldSource::HoldFunds {} => {}
+            #[cfg(feature = "mock-market")]
+            YieldSource::MockMarket { exchange_rate } => {
+                if exchange_rate.is_zero() {
+                    return Err(cosmwasm_std::StdError::generic_err("Exchange rate must be positive"));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the stable coin value of a share, e.g. the aUST exchange rate
+    pub fn exchange_rate(&self, deps: Deps) -> StdResult<Decimal> {
+        match self {
+            YieldSource::Anchor { anchor_addr } => anchor::query_exchange_rate(deps, anchor_addr),
+            YieldSource::HoldFunds {} => Ok(Decimal::one()),
+            #[cfg(feature = "mock-market")]
+            YieldSource::MockMarket { exchange_rate } => Ok(*exchange_rate),
+        }
+    }
+
+    /// Deposits the coin in exchange for shares
+    pub fn deposit(&self, deps: Deps, coin: Coin) -> StdResult<YieldAction> {
+        match self {
+            YieldSource::Anchor { anchor_addr } => {
+                Ok(YieldAction::Pending(anchor::deposit_msg(deps, anchor_addr, coin)?))
+            }
+            YieldSource::HoldFunds {} => Ok(YieldAction::Settled {
+                amount: coin.amount,
+                shares: coin.amount,
+            }),
+            #[cfg(feature = "mock-market")]
+            YieldSource::MockMarket { exchange_rate } => Ok(YieldAction::Settled {
+                amount: coin.amount,
+                shares: to_shares(coin.amount, *exchange_rate),
+            }),
+        }
+    }
+
+    /// Redeems shares for stable coins sent back to the contract
+    pub fn redeem(&self, deps: Deps, shares: Uint128) -> StdResult<YieldAction> {
+        match self {
+            YieldSource::Anchor { anchor_addr } => {
+                Ok(YieldAction::Pending(anchor::redeem_msg(deps, anchor_addr, shares)?))
+            }
+            YieldSource::HoldFunds {} => Ok(YieldAction::Settled {
+                amount: shares,
+                shares,
+            }),
+            #[cfg(feature = "mock-market")]
+            YieldSource::MockMarket { exchange_rate } => Ok(YieldAction::Settled {
+                amount: shares * *exchange_rate,
+                shares,
+            }),
+        }
+    }
+
+    /// Returns true if the deposits have to be moved when switching to the other source.
+    /// A mock market only changes its exchange rate, which simulates accrued yield
+    pub fn requires_migration(&self, other: &YieldSource) -> bool {
+        #[cfg(feature = "mock-market")]
+        if let (YieldSource::MockMarket { .. }, YieldSource::MockMarket { .. }) = (self, other) {
+            return false;
+        }
+        #[cfg(not(feature = "mock-market"))]
+        let _ = other;
+
+        true
+    }
+}
+
+/// Returns the number of shares worth the amount at the exchange rate
+pub fn to_shares(amount: Uint128, exchange_rate: Decimal) -> Uint128 {
+    amount.multiply_ratio(DECIMAL_FRACTION, DECIMAL_FRACTION * exchange_rate)
+}